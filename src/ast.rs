@@ -3,7 +3,7 @@
 use proc_macro2::Term;
 use syn::punctuated::Punctuated;
 use syn::{Attribute, Lit};
-use syn::{Block, FieldsNamed, FnArg, Ident, Path, ReturnType, Type};
+use syn::{Block, FieldsNamed, FnArg, Ident, Path, ReturnType, Type, Variant};
 
 pub struct Program {
     pub items: Vec<Item>,
@@ -30,12 +30,28 @@ impl Program {
             _ => None,
         })
     }
+
+    pub fn enums<'a>(&'a self) -> impl Iterator<Item = &'a Enum> + 'a {
+        self.items.iter().filter_map(|item| match *item {
+            Item::Enum(ref e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn boxed<'a>(&'a self) -> impl Iterator<Item = &'a Boxed> + 'a {
+        self.items.iter().filter_map(|item| match *item {
+            Item::Boxed(ref b) => Some(b),
+            _ => None,
+        })
+    }
 }
 
 pub enum Item {
     Class(Class),
     Impl(Impl),
     Interface(Interface),
+    Enum(Enum),
+    Boxed(Boxed),
 }
 
 pub fn get_program_classes<'a>(program: &'a Program) -> Vec<&'a Class> {
@@ -55,6 +71,7 @@ pub fn get_program_classes<'a>(program: &'a Program) -> Vec<&'a Class> {
 pub struct Class {
     pub name: Ident,
     pub extends: Option<Path>,
+    pub implements: Vec<Path>, // the paths after the superclass
     pub fields: FieldsNamed,
 }
 
@@ -68,10 +85,26 @@ pub struct Impl {
 
 pub struct Interface {
     pub name: Ident,
-    // FIXME: required class and interfaces
+    pub requires: Vec<Path>, // prerequisite class and interfaces
     pub items: Vec<ImplItem>,
 }
 
+// similar to syn::ItemEnum, but only for C-like enums; `flags Foo { ... }`
+// uses the same syntax
+pub struct Enum {
+    pub is_flags: bool,
+    pub name: Ident,
+    pub variants: Punctuated<Variant, Token!(,)>,
+}
+
+// `boxed struct Foo { ... }` defines a new struct; `boxed Foo;` registers
+// an existing type
+pub struct Boxed {
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub fields: Option<FieldsNamed>,
+}
+
 pub struct ImplItem {
     pub attrs: Vec<Attribute>,
     pub node: ImplItemKind,
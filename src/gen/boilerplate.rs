@@ -32,7 +32,8 @@ impl<'ast> ClassContext<'ast> {
         let init_priv_with_default = self.init_priv_with_default();
         let free_instance_private = self.free_instance_private();
         let get_type_fn_name = self.instance_get_type_fn_name();
-        let imp_new_fn_name = self.imp_new_fn_name();
+        let imp_new_fn = self.imp_new_fn();
+        let constructor = self.constructor();
         let private_fields = &self.class.private_fields;
 
         let slots = self.slots();
@@ -41,9 +42,14 @@ impl<'ast> ClassContext<'ast> {
         let signal_id_names = &names; // reference, otherwise quote! will consume the vector
 
         let slot_default_handlers = self.imp_slot_default_handlers();
+        let parent_chain_up_fns = self.imp_parent_chain_up_fns();
         let signal_emit_methods = self.signal_emit_methods();
+        let signal_id_fns = self.signal_id_fns();
         let slot_assignments = self.slot_assignments();
         let signal_declarations = self.signal_declarations();
+        let signal_accumulator_trampolines = self.signal_accumulator_trampolines();
+        let interface_init_fns = self.interface_init_fns();
+        let interface_registrations = self.interface_registrations();
 
         let instance_slot_trampolines = self.instance_slot_trampolines();
         let instance_name_string = CStringIdent(*InstanceName);
@@ -55,12 +61,26 @@ impl<'ast> ClassContext<'ast> {
         let signal_trampolines = self.signal_trampolines();
 
         let properties_enum = self.properties_enum();
-        // let properties_setters = &self.properties_setter();
+        let property_default_handlers = self.property_default_handlers();
+        let property_pspec_fns = self.property_pspec_fns();
+        let property_notify_fns = self.property_notify_fns();
+        let property_vfuncs = self.property_vfuncs();
+        let properties_registration = self.properties_registration();
+        let property_trait_fns = self.property_trait_fns();
+        let property_trait_impls = self.property_trait_impls();
+        let property_notify_trampoline = self.property_notify_trampoline();
+
+        // The wrapper is IsA<> its parent class and its interfaces
+        let mut parents = Vec::new();
+        if !self.class.gobject_parent {
+            parents.push(quote_cs! { #ParentInstance });
+        }
+        parents.extend(self.class.implements.iter().map(|iface| quote_cs! { #iface }));
 
-        let parent_instance_tokens = if self.class.gobject_parent {
+        let parent_instance_tokens = if parents.is_empty() {
             quote_cs!{}
         } else {
-            quote_cs! { : #ParentInstance }
+            quote_cs! { : #(#parents),* }
         };
 
         quote_cs! {
@@ -142,7 +162,7 @@ impl<'ast> ClassContext<'ast> {
 
                     struct #PrivateClassName {
                         parent_class: *const #ParentClassFfi,
-                        // properties:   *const Vec<*const gobject_ffi::GParamSpec>,
+                        properties:   *const Vec<*mut gobject_ffi::GParamSpec>,
 
                         // signal ids
                         #(#signal_id_names: u32,)*
@@ -152,7 +172,7 @@ impl<'ast> ClassContext<'ast> {
                         // we use this instead of "ptr::null()" because using
                         // function calls to set constants is feature-gated.
                         parent_class: 0 as *const _,
-                        // properties:   0 as *const _,
+                        properties:   0 as *const _,
 
                         // signal ids
                         #(#signal_id_names: 0,)*
@@ -172,7 +192,17 @@ impl<'ast> ClassContext<'ast> {
 
                         #(#slot_default_handlers)*
 
+                        #(#parent_chain_up_fns)*
+
+                        #(#property_default_handlers)*
+
+                        #(#property_pspec_fns)*
+
+                        #(#property_notify_fns)*
+
                         #(#signal_emit_methods)*
+
+                        #(#signal_id_fns)*
                     }
 
                     impl #InstanceNameFfi {
@@ -201,37 +231,7 @@ impl<'ast> ClassContext<'ast> {
                             (*(PRIV.parent_class as *mut gobject_ffi::GObjectClass)).finalize.map(|f| f(obj));
                         }
 
-                        //unsafe extern "C" fn set_property(obj: *mut gobject_ffi::GObject,
-                        //                                  property_id: u32,
-                        //                                  value: gobject_ffi::GValue,
-                        //                                  pspec: gobject_ffi::GParamSpec) {
-
-                        //  ViewerFile *self = VIEWER_FILE (object);
-
-                        //  switch (property_id)
-                        //    {
-
-                        //    #(#properties_setters)*
-
-                        //    case PROP_FILENAME:
-                        //      g_free (self->priv->filename);
-                        //      self->priv->filename = g_value_dup_string (value);
-                        //      g_print ("filename: %s\n", self->priv->filename);
-                        //      break;
-
-                        //    case PROP_ZOOM_LEVEL:
-                        //      self->priv->zoom_level = g_value_get_uint (value);
-                        //      g_print ("zoom level: %u\n", self->priv->zoom_level);
-                        //      break;
-
-                        //    default:
-                        //      /* We don't have any other property... */
-                        //      G_OBJECT_WARN_INVALID_PROPERTY_ID (object, property_id, pspec);
-                        //      break;
-                        //    }
-                        //}
-
-                        // FIXME: get_property() handler
+                        #property_vfuncs
 
                         #(#instance_slot_trampolines)*
                     }
@@ -246,14 +246,8 @@ impl<'ast> ClassContext<'ast> {
                             {
                                 let gobject_class = &mut *(klass as *mut gobject_ffi::GObjectClass);
                                 gobject_class.finalize = Some(#InstanceNameFfi::finalize);
-                                // FIXME: gobject_class.set_property = Some(#InstanceNameFfi::set_property);
-                                // FIXME: gobject_class.get_property = Some(#InstanceNameFfi::get_property);
-
-                                // FIXME
-                                // let mut properties = Vec::new();
-                                //
-                                // create each property
 
+                                #properties_registration
                             }
 
                             // Slots
@@ -270,20 +264,13 @@ impl<'ast> ClassContext<'ast> {
 
                             PRIV.parent_class = gobject_ffi::g_type_class_peek_parent(klass) as *const #ParentClassFfi;
                         }
-                    }
 
-                    #[no_mangle]
-                    pub unsafe extern "C" fn #imp_new_fn_name(/* FIXME: args */) -> *mut #InstanceNameFfi {
-                        #callback_guard
+                        #(#interface_init_fns)*
+                    }
 
-                        let this = gobject_ffi::g_object_newv(
-                            #get_type_fn_name(),
-                            0,              // FIXME: num_arguments
-                            ptr::null_mut() // FIXME: args
-                        );
+                    #(#signal_accumulator_trampolines)*
 
-                        this as *mut #InstanceNameFfi
-                    }
+                    #imp_new_fn
 
                     #(#imp_extern_methods)*
 
@@ -314,7 +301,7 @@ impl<'ast> ClassContext<'ast> {
                                 gobject_ffi::GTypeFlags::empty()
                             );
 
-                            // FIXME: add interfaces
+                            #(#interface_registrations)*
                         });
 
                         TYPE
@@ -322,44 +309,23 @@ impl<'ast> ClassContext<'ast> {
 
                 }
 
-                impl #InstanceName {
-                    // FIXME: we should take construct-only arguments and other convenient args to new()
-                    pub fn new() -> #InstanceName {
-                        unsafe { from_glib_full(imp::#imp_new_fn_name(/* FIXME: args */)) }
-                    }
-                }
+                #constructor
 
                 pub trait #InstanceExt {
                     #(#slot_trait_fns)*
 
-                    // FIXME: property setters/getters like in glib-rs
-                    //
-                    // fn get_property_foo(&self) -> type;
-                    //
-                    // fn set_property_foo(&self, v: type);
+                    #(#property_trait_fns)*
                 }
 
                 impl<O: IsA<#InstanceName> + IsA<glib::object::Object> + glib::object::ObjectExt> #InstanceExt for O {
                     #(#slot_trait_impls)*
 
-                    // FIXME: property setters/getters like in glib-rs
-                    //
-                    // fn get_property_foo(&self) -> type {
-                    //     let mut value = Value::from(&false); // FIXME: Value::from(&what)?
-                    //     unsafe {
-                    //         gobject_ffi:g_object_get_property(self.to_glib_none().0, "foo".to_glib_none().0, value.to_glib_none_mut().0);
-                    //     }
-                    //     value.get().unwrap()
-                    // }
-                    //
-                    // fn set_property_foo(&self, v: type) {
-                    //     unsafe {
-                    //         gobject_ffi:g_object_set_property(self.to_glib_none().0, "foo".to_glib_none().0, Value::from(&v).to_glib_none().0);
-                    //     }
-                    // }
+                    #(#property_trait_impls)*
                 }
 
                 #(#signal_trampolines)*
+
+                #property_notify_trampoline
             }
 
             pub use self::#ModuleName::*;
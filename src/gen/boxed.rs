@@ -0,0 +1,156 @@
+// We give `ClassName` variables an identifier that uses upper-case.
+#![allow(non_snake_case)]
+
+use proc_macro2::Span;
+use quote::Tokens;
+use syn::Ident;
+
+use glib_utils::*;
+
+use gen::WithSuffix;
+use hir::{Boxed, Program};
+
+use super::cstringident::CStringIdent;
+
+pub struct BoxedContext<'ast> {
+    pub program: &'ast Program<'ast>,
+    pub boxed: &'ast Boxed<'ast>,
+}
+
+impl<'ast> BoxedContext<'ast> {
+    pub fn new(program: &'ast Program, boxed: &'ast Boxed) -> Self {
+        BoxedContext { program, boxed }
+    }
+
+    pub fn gen_boxed(&self) -> Tokens {
+        let ModuleName = self.boxed.name.with_suffix("Mod");
+        let Name = &self.boxed.name;
+        let name_string = CStringIdent(*Name);
+        let get_type_fn_name = self.get_type_fn_name();
+        let callback_guard = glib_callback_guard();
+
+        let definition = match self.boxed.definition {
+            Some((attrs, fields)) => quote_cs! {
+                #(#attrs)*
+                pub struct #Name #fields
+            },
+            None => quote_cs! {},
+        };
+
+        quote_cs! {
+            pub mod #ModuleName {
+                #![allow(non_snake_case)] // "oddly" named module above
+                extern crate glib_sys as glib_ffi;
+                extern crate gobject_sys as gobject_ffi;
+
+                extern crate glib;
+
+                // Bring in the types of the struct's fields, or the type
+                // itself for `boxed Foo;`
+                #[allow(unused_imports)]
+                use super::*;
+
+                use glib::translate::*;
+                use glib::value::{FromValue, FromValueOptional, SetValue, SetValueOptional, Value};
+                use std::ptr;
+
+                #definition
+
+                impl glib::StaticType for #Name {
+                    fn static_type() -> glib::Type {
+                        unsafe { from_glib(imp::#get_type_fn_name()) }
+                    }
+                }
+
+                impl<'a> FromValueOptional<'a> for #Name {
+                    unsafe fn from_value_optional(value: &Value) -> Option<Self> {
+                        let boxed = gobject_ffi::g_value_get_boxed(value.to_glib_none().0);
+                        if boxed.is_null() {
+                            None
+                        } else {
+                            Some(Clone::clone(&*(boxed as *const #Name)))
+                        }
+                    }
+                }
+
+                // Only for values known not to be NULL; the generated code
+                // uses from_value_optional() for values that come from C
+                impl<'a> FromValue<'a> for #Name {
+                    unsafe fn from_value(value: &Value) -> Self {
+                        FromValueOptional::from_value_optional(value).unwrap()
+                    }
+                }
+
+                impl SetValue for #Name {
+                    unsafe fn set_value(value: &mut Value, this: &Self) {
+                        // GObject makes a copy with our copy function
+                        gobject_ffi::g_value_set_boxed(
+                            value.to_glib_none_mut().0,
+                            this as *const #Name as glib_ffi::gconstpointer,
+                        )
+                    }
+                }
+
+                impl SetValueOptional for #Name {
+                    unsafe fn set_value_optional(value: &mut Value, this: Option<&Self>) {
+                        gobject_ffi::g_value_set_boxed(
+                            value.to_glib_none_mut().0,
+                            this.map_or(ptr::null(), |this| this as *const #Name as glib_ffi::gconstpointer),
+                        )
+                    }
+                }
+
+                pub mod imp {
+                    use super::glib_ffi;
+                    use super::gobject_ffi;
+
+                    use super::#Name;
+
+                    // Boxed values are allocated with Box, so that the C
+                    // API and the generated code can exchange them freely.
+                    unsafe extern "C" fn copy(boxed: glib_ffi::gpointer) -> glib_ffi::gpointer {
+                        #callback_guard
+
+                        let boxed = &*(boxed as *const #Name);
+                        Box::into_raw(Box::new(Clone::clone(boxed))) as glib_ffi::gpointer
+                    }
+
+                    unsafe extern "C" fn free(boxed: glib_ffi::gpointer) {
+                        #callback_guard
+
+                        drop(Box::from_raw(boxed as *mut #Name));
+                    }
+
+                    #[no_mangle]
+                    pub unsafe extern "C" fn #get_type_fn_name() -> glib_ffi::GType {
+                        #callback_guard
+
+                        use std::sync::{Once, ONCE_INIT};
+
+                        static mut TYPE: glib_ffi::GType = gobject_ffi::G_TYPE_INVALID;
+                        static ONCE: Once = ONCE_INIT;
+
+                        ONCE.call_once(|| {
+                            TYPE = gobject_ffi::g_boxed_type_register_static(
+                                #name_string as *const u8 as *const i8,
+                                Some(copy),
+                                Some(free),
+                            );
+                        });
+
+                        TYPE
+                    }
+                }
+            }
+
+            pub use self::#ModuleName::*;
+        }
+    }
+
+    fn get_type_fn_name(&self) -> Ident {
+        Ident::new(
+            &format!("{}_get_type", lower_case_instance_name(self.boxed.name.as_ref())),
+            Span::call_site(),
+        )
+    }
+}
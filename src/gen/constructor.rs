@@ -0,0 +1,174 @@
+use quote::Tokens;
+use syn::Ident;
+
+use glib_utils::*;
+use hir::{FnArg, FnSig, Property, Ty};
+use param::canonical_param_name;
+
+use super::class::ClassContext;
+use super::cstringident::CStringLiteral;
+use super::values;
+
+impl<'ast> ClassContext<'ast> {
+    /// Generates the exported `foo_new()` function callable from C.
+    /// It takes the constructor arguments with Glib types and calls
+    /// `Foo::new()`.
+    pub fn imp_new_fn(&self) -> Tokens {
+        let InstanceName = self.InstanceName;
+        let InstanceNameFfi = &self.InstanceNameFfi;
+        let imp_new_fn_name = self.imp_new_fn_name();
+        let callback_guard = glib_callback_guard();
+
+        let sig = self.constructor_sig();
+        let c_inputs = sig.input_args_with_glib_types();
+        let args = sig.input_args_from_glib_types();
+
+        quote_cs! {
+            #[no_mangle]
+            pub unsafe extern "C" fn #imp_new_fn_name(#c_inputs) -> *mut #InstanceNameFfi {
+                #callback_guard
+
+                super::#InstanceName::new(#args).to_glib_full()
+            }
+        }
+    }
+
+    /// Generates `Foo::new()`, `Foo::builder()` and the `FooBuilder`
+    /// struct.
+    ///
+    /// `Foo::new()` takes one argument for each property marked with
+    /// `#[constructor_arg]`.  The builder has a method for each
+    /// writable property; the ones that don't get called are left to
+    /// their defaults.  Both end up creating the object with
+    /// `g_object_newv()`, so construct properties are set before the
+    /// object is handed back.
+    pub fn constructor(&self) -> Tokens {
+        let InstanceName = self.InstanceName;
+        let InstanceNameFfi = &self.InstanceNameFfi;
+        let BuilderName = self.builder_name();
+        let get_type_fn_name = self.instance_get_type_fn_name();
+
+        let inputs = self.constructor_args().map(|prop| {
+            let name = property_arg_name(prop);
+            let type_ = &prop.type_;
+            quote_cs! { #name: #type_ }
+        });
+        let builder_calls = self.constructor_args().map(|prop| {
+            let name = property_arg_name(prop);
+            quote_cs! { .#name(#name) }
+        });
+
+        let builder_methods = self.class
+            .properties
+            .iter()
+            .filter(|prop| prop.is_writable())
+            .map(|prop| {
+                let name = property_arg_name(prop);
+                let type_ = &prop.type_;
+                let param_name = CStringLiteral(&canonical_param_name(prop.name.as_ref()));
+                let value = values::to_value(type_, &quote_cs! { v });
+
+                quote_cs! {
+                    pub fn #name(self, v: #type_) -> Self {
+                        self.set(&#param_name[..], #value)
+                    }
+                }
+            });
+
+        quote_cs! {
+            impl #InstanceName {
+                pub fn new(#(#inputs),*) -> #InstanceName {
+                    #InstanceName::builder()
+                        #(#builder_calls)*
+                        .build()
+                }
+
+                pub fn builder() -> #BuilderName {
+                    #BuilderName {
+                        properties: Vec::new(),
+                    }
+                }
+            }
+
+            /// Builder for creating an object with some of its
+            /// properties set.  Create one with the class's `builder()`
+            /// function.
+            pub struct #BuilderName {
+                // NUL-terminated property names and their values
+                properties: Vec<(&'static [u8], glib::Value)>,
+            }
+
+            impl #BuilderName {
+                #(#builder_methods)*
+
+                #[allow(dead_code)] // not used if there are no writable properties
+                fn set(mut self, name: &'static [u8], value: glib::Value) -> Self {
+                    // GObject complains if a property is passed twice
+                    self.properties.retain(|&(n, _)| n != name);
+                    self.properties.push((name, value));
+                    self
+                }
+
+                pub fn build(self) -> #InstanceName {
+                    unsafe {
+                        // The GValues are still owned by self.properties;
+                        // the GParameters just borrow them during the call.
+                        let mut parameters: Vec<gobject_ffi::GParameter> = self.properties
+                            .iter()
+                            .map(|&(name, ref value)| gobject_ffi::GParameter {
+                                name: name.as_ptr() as *const i8,
+                                value: ptr::read(value.to_glib_none().0),
+                            })
+                            .collect();
+
+                        let this = gobject_ffi::g_object_newv(
+                            imp::#get_type_fn_name(),
+                            parameters.len() as u32,
+                            parameters.as_mut_ptr(),
+                        );
+
+                        from_glib_full(this as *mut imp::#InstanceNameFfi)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a signature for `Foo::new()`, with an argument for each
+    /// property marked with `#[constructor_arg]`.
+    ///
+    /// The signature starts with a dummy `&self` because the `FnSig`
+    /// helpers for generating argument lists skip the first argument.
+    fn constructor_sig(&self) -> FnSig<'ast> {
+        let mut inputs = vec![FnArg::SelfRef(Default::default(), Default::default())];
+
+        inputs.extend(self.constructor_args().map(|prop| FnArg::Arg {
+            mutbl: None,
+            name: property_arg_name(prop),
+            ty: prop.type_.clone(),
+        }));
+
+        FnSig {
+            name: Ident::from("new"),
+            inputs,
+            output: Ty::Unit,
+        }
+    }
+
+    fn constructor_args(&self) -> impl Iterator<Item = &'ast Property<'ast>> {
+        self.class
+            .properties
+            .iter()
+            .filter(|prop| prop.constructor_arg)
+    }
+
+    fn builder_name(&self) -> Ident {
+        Ident::from(format!("{}Builder", self.InstanceName.as_ref()))
+    }
+}
+
+/// From a property called `Foo`, generate `foo`.  This is used for the
+/// property's argument in `new()` and for its method in the builder.
+fn property_arg_name(prop: &Property) -> Ident {
+    Ident::from(lower_case_instance_name(prop.name.as_ref()))
+}
@@ -18,3 +18,17 @@ impl ToTokens for CStringIdent {
         tokens.append(TokenTree::Literal(Literal::byte_string(&v)));
     }
 }
+
+/// Like `CStringIdent`, but for an arbitrary string.
+///
+/// This is useful for names which are not valid Rust identifiers,
+/// like the canonical names of GObject properties (`"my-prop"`).
+pub struct CStringLiteral<'a>(pub &'a str);
+
+impl<'a> ToTokens for CStringLiteral<'a> {
+    fn to_tokens(&self, tokens: &mut Tokens) {
+        let mut v = Vec::from(self.0.as_bytes());
+        v.push(0u8);
+        tokens.append(TokenTree::Literal(Literal::byte_string(&v)));
+    }
+}
@@ -0,0 +1,449 @@
+// We give `ClassName` variables an identifier that uses upper-case.
+#![allow(non_snake_case)]
+
+use proc_macro2::{Literal, Span};
+use quote::Tokens;
+use syn::Ident;
+
+use glib_utils::*;
+use param::canonical_param_name;
+
+use gen::{g_critical, WithSuffix};
+use hir::{Enum, Program};
+
+use super::cstringident::{CStringIdent, CStringLiteral};
+
+pub struct EnumContext<'ast> {
+    pub program: &'ast Program<'ast>,
+    pub enum_: &'ast Enum,
+}
+
+impl<'ast> EnumContext<'ast> {
+    pub fn new(program: &'ast Program, enum_: &'ast Enum) -> Self {
+        EnumContext { program, enum_ }
+    }
+
+    pub fn gen_enum(&self) -> Tokens {
+        let ModuleName = self.enum_.name.with_suffix("Mod");
+        let Name = &self.enum_.name;
+        let name_string = CStringIdent(*Name);
+        let get_type_fn_name = self.get_type_fn_name();
+        let callback_guard = glib_callback_guard();
+
+        let (type_definition, value_getter, value_setter, register_fn, value_struct) =
+            if self.enum_.is_flags {
+                (
+                    self.flags_definition(),
+                    quote_cs! { g_value_get_flags },
+                    quote_cs! { g_value_set_flags },
+                    quote_cs! { g_flags_register_static },
+                    quote_cs! { GFlagsValue },
+                )
+            } else {
+                (
+                    self.enum_definition(),
+                    quote_cs! { g_value_get_enum },
+                    quote_cs! { g_value_set_enum },
+                    quote_cs! { g_enum_register_static },
+                    quote_cs! { GEnumValue },
+                )
+            };
+        let values = self.values(&value_struct);
+
+        quote_cs! {
+            pub mod #ModuleName {
+                #![allow(non_snake_case)] // "oddly" named module above
+                extern crate glib_sys as glib_ffi;
+                extern crate gobject_sys as gobject_ffi;
+
+                extern crate glib;
+
+                use glib::translate::*;
+                use glib::value::{FromValue, FromValueOptional, SetValue, Value};
+
+                #type_definition
+
+                impl glib::StaticType for #Name {
+                    fn static_type() -> glib::Type {
+                        unsafe { from_glib(imp::#get_type_fn_name()) }
+                    }
+                }
+
+                impl<'a> FromValueOptional<'a> for #Name {
+                    unsafe fn from_value_optional(value: &Value) -> Option<Self> {
+                        Some(FromValue::from_value(value))
+                    }
+                }
+
+                impl<'a> FromValue<'a> for #Name {
+                    unsafe fn from_value(value: &Value) -> Self {
+                        from_glib(gobject_ffi::#value_getter(value.to_glib_none().0))
+                    }
+                }
+
+                impl SetValue for #Name {
+                    unsafe fn set_value(value: &mut Value, this: &Self) {
+                        gobject_ffi::#value_setter(value.to_glib_none_mut().0, this.to_glib())
+                    }
+                }
+
+                pub mod imp {
+                    use super::glib;
+                    use super::glib_ffi;
+                    use super::gobject_ffi;
+
+                    use std::ptr;
+
+                    #[no_mangle]
+                    pub unsafe extern "C" fn #get_type_fn_name() -> glib_ffi::GType {
+                        #callback_guard
+
+                        use std::sync::{Once, ONCE_INIT};
+
+                        static mut TYPE: glib_ffi::GType = gobject_ffi::G_TYPE_INVALID;
+                        static ONCE: Once = ONCE_INIT;
+
+                        ONCE.call_once(|| {
+                            // GObject keeps a pointer to the values, so leak them
+                            let values = Box::new([
+                                #(#values,)*
+                                gobject_ffi::#value_struct {
+                                    value: 0,
+                                    value_name: ptr::null(),
+                                    value_nick: ptr::null(),
+                                },
+                            ]);
+
+                            TYPE = gobject_ffi::#register_fn(
+                                #name_string as *const u8 as *const i8,
+                                Box::into_raw(values) as *const gobject_ffi::#value_struct,
+                            );
+                        });
+
+                        TYPE
+                    }
+                }
+            }
+
+            pub use self::#ModuleName::*;
+        }
+    }
+
+    /// Generates a C-like Rust enum, and its conversions to and from
+    /// the `gint` that GObject uses for enum values.
+    fn enum_definition(&self) -> Tokens {
+        let Name = &self.enum_.name;
+
+        let variant_names = &self.enum_
+            .variants
+            .iter()
+            .map(|v| v.name)
+            .collect::<Vec<_>>();
+        let variant_values = &self.enum_
+            .variants
+            .iter()
+            .map(|v| enum_value_tokens(v.value as i32))
+            .collect::<Vec<_>>();
+        let from_glib_arms = variant_names
+            .iter()
+            .zip(variant_values)
+            .map(|(name, value)| quote_cs! { #value => #Name::#name, })
+            .collect::<Vec<_>>();
+
+        // Values come from C, so an unknown one is not worth a panic
+        let first = variant_names[0];
+        let critical = g_critical(
+            &format!("invalid value %d for enum {}", Name.as_ref()),
+            quote_cs! { value },
+        );
+
+        quote_cs! {
+            #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+            pub enum #Name {
+                #(#variant_names = #variant_values,)*
+            }
+
+            impl ToGlib for #Name {
+                type GlibType = i32;
+
+                fn to_glib(&self) -> i32 {
+                    *self as i32
+                }
+            }
+
+            impl FromGlib<i32> for #Name {
+                fn from_glib(value: i32) -> Self {
+                    match value {
+                        #(#from_glib_arms)*
+                        _ => {
+                            unsafe { #critical; }
+                            #Name::#first
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates a struct in the style of the `bitflags` crate, with an
+    /// associated constant for each flag, and its conversions to and
+    /// from the `guint` that GObject uses for flags values.
+    fn flags_definition(&self) -> Tokens {
+        let Name = &self.enum_.name;
+
+        let all = self.enum_.variants.iter().fold(0, |all, v| all | v.value as u32);
+        let all = Literal::u32_unsuffixed(all);
+
+        let constants = self.enum_
+            .variants
+            .iter()
+            .map(|v| {
+                let name = v.name;
+                let value = Literal::u32_unsuffixed(v.value as u32);
+                quote_cs! { pub const #name: #Name = #Name { bits: #value }; }
+            })
+            .collect::<Vec<_>>();
+
+        let debug_flags = self.enum_
+            .variants
+            .iter()
+            .filter(|v| v.value != 0)
+            .map(|v| {
+                let name = v.name;
+                quote_cs! {
+                    if self.contains(#Name::#name) {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        first = false;
+                        f.write_str(stringify!(#name))?;
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        quote_cs! {
+            #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct #Name {
+                bits: u32,
+            }
+
+            #[allow(non_upper_case_globals)]
+            impl #Name {
+                #(#constants)*
+
+                /// Returns an empty set of flags.
+                pub fn empty() -> #Name {
+                    #Name { bits: 0 }
+                }
+
+                /// Returns the set containing all flags.
+                pub fn all() -> #Name {
+                    #Name { bits: #all }
+                }
+
+                /// Returns the raw value of the flags.
+                pub fn bits(&self) -> u32 {
+                    self.bits
+                }
+
+                /// Converts from a raw value, unless it has bits that
+                /// don't correspond to any flag.
+                pub fn from_bits(bits: u32) -> Option<#Name> {
+                    if bits & !#all == 0 {
+                        Some(#Name { bits })
+                    } else {
+                        None
+                    }
+                }
+
+                /// Converts from a raw value, dropping any bits that don't
+                /// correspond to a flag.
+                pub fn from_bits_truncate(bits: u32) -> #Name {
+                    #Name { bits: bits & #all }
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.bits == 0
+                }
+
+                pub fn is_all(&self) -> bool {
+                    self.bits == #all
+                }
+
+                pub fn contains(&self, other: #Name) -> bool {
+                    self.bits & other.bits == other.bits
+                }
+
+                pub fn intersects(&self, other: #Name) -> bool {
+                    self.bits & other.bits != 0
+                }
+
+                pub fn insert(&mut self, other: #Name) {
+                    self.bits |= other.bits;
+                }
+
+                pub fn remove(&mut self, other: #Name) {
+                    self.bits &= !other.bits;
+                }
+
+                pub fn toggle(&mut self, other: #Name) {
+                    self.bits ^= other.bits;
+                }
+
+                pub fn set(&mut self, other: #Name, value: bool) {
+                    if value {
+                        self.insert(other);
+                    } else {
+                        self.remove(other);
+                    }
+                }
+            }
+
+            impl ::std::ops::BitOr for #Name {
+                type Output = #Name;
+
+                fn bitor(self, other: #Name) -> #Name {
+                    #Name { bits: self.bits | other.bits }
+                }
+            }
+
+            impl ::std::ops::BitOrAssign for #Name {
+                fn bitor_assign(&mut self, other: #Name) {
+                    self.bits |= other.bits;
+                }
+            }
+
+            impl ::std::ops::BitAnd for #Name {
+                type Output = #Name;
+
+                fn bitand(self, other: #Name) -> #Name {
+                    #Name { bits: self.bits & other.bits }
+                }
+            }
+
+            impl ::std::ops::BitAndAssign for #Name {
+                fn bitand_assign(&mut self, other: #Name) {
+                    self.bits &= other.bits;
+                }
+            }
+
+            impl ::std::ops::BitXor for #Name {
+                type Output = #Name;
+
+                fn bitxor(self, other: #Name) -> #Name {
+                    #Name { bits: self.bits ^ other.bits }
+                }
+            }
+
+            impl ::std::ops::BitXorAssign for #Name {
+                fn bitxor_assign(&mut self, other: #Name) {
+                    self.bits ^= other.bits;
+                }
+            }
+
+            impl ::std::ops::Sub for #Name {
+                type Output = #Name;
+
+                fn sub(self, other: #Name) -> #Name {
+                    #Name { bits: self.bits & !other.bits }
+                }
+            }
+
+            impl ::std::ops::SubAssign for #Name {
+                fn sub_assign(&mut self, other: #Name) {
+                    self.bits &= !other.bits;
+                }
+            }
+
+            impl ::std::ops::Not for #Name {
+                type Output = #Name;
+
+                fn not(self) -> #Name {
+                    #Name::from_bits_truncate(!self.bits)
+                }
+            }
+
+            impl ::std::fmt::Debug for #Name {
+                #[allow(unused_mut, unused_assignments)]
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    let mut first = true;
+                    #(#debug_flags)*
+                    if first {
+                        f.write_str("(empty)")?;
+                    }
+                    Ok(())
+                }
+            }
+
+            impl ToGlib for #Name {
+                type GlibType = u32;
+
+                fn to_glib(&self) -> u32 {
+                    self.bits
+                }
+            }
+
+            impl FromGlib<u32> for #Name {
+                fn from_glib(value: u32) -> Self {
+                    #Name::from_bits_truncate(value)
+                }
+            }
+        }
+    }
+
+    fn get_type_fn_name(&self) -> Ident {
+        Ident::new(
+            &format!("{}_get_type", lower_case_instance_name(self.enum_.name.as_ref())),
+            Span::call_site(),
+        )
+    }
+
+    /// Generates the `GEnumValue` or `GFlagsValue` for each variant.
+    /// Like in C, the name of a value is `TYPE_NAME_VARIANT_NAME`, and
+    /// its nick is the variant's name in lower case, like
+    /// `variant-name`.
+    fn values(&self, value_struct: &Tokens) -> Vec<Tokens> {
+        let type_name = lower_case_instance_name(self.enum_.name.as_ref()).to_uppercase();
+
+        self.enum_
+            .variants
+            .iter()
+            .map(|variant| {
+                let value = if self.enum_.is_flags {
+                    let value = Literal::u32_unsuffixed(variant.value as u32);
+                    quote_cs! { #value }
+                } else {
+                    enum_value_tokens(variant.value as i32)
+                };
+                let name = format!(
+                    "{}_{}",
+                    type_name,
+                    lower_case_instance_name(variant.name.as_ref()).to_uppercase()
+                );
+                let name = CStringLiteral(&name);
+                let nick = canonical_param_name(variant.name.as_ref());
+                let nick = CStringLiteral(&nick);
+
+                quote_cs! {
+                    gobject_ffi::#value_struct {
+                        value: #value,
+                        value_name: #name as *const u8 as *const i8,
+                        value_nick: #nick as *const u8 as *const i8,
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Generates an enum value as an integer literal.  Negative values need
+/// a separate `-`, since a literal token can't have a sign.
+fn enum_value_tokens(value: i32) -> Tokens {
+    let abs = Literal::u32_unsuffixed((value as i64).abs() as u32);
+    if value < 0 {
+        quote_cs! { -#abs }
+    } else {
+        quote_cs! { #abs }
+    }
+}
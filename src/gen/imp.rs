@@ -2,7 +2,9 @@ use quote::Tokens;
 use syn::{Block, Ident};
 
 use glib_utils::*;
-use hir::{FnSig, Method, Signal, Slot, VirtualMethod};
+
+use gen::WithSuffix;
+use hir::{FnSig, Method, Override, Signal, Slot, Ty, VirtualMethod};
 
 use super::class::ClassContext;
 
@@ -95,22 +97,12 @@ impl<'ast> ClassContext<'ast> {
                 Slot::Signal(Signal {
                     ref sig,
                     body: Some(body),
+                    ..
                 }) => method(sig, body, None),
 
-                Slot::Signal(Signal {
-                    ref sig,
-                    body: None,
-                }) => {
-                    let name = Self::slot_impl_name(&sig.name);
-                    let inputs = &sig.inputs;
-                    let output = &sig.output;
-                    quote_cs! {
-                        #[allow(unused_variables)] // since none of the inputs will be used
-                        fn #name(#(#inputs),*) -> #output {
-                            panic!("Called default signal handler {} with no implementation", stringify!(#name));
-                        }
-                    }
-                }
+                // Signals without a default handler leave their class slot
+                // empty, so their class closure doesn't do anything
+                Slot::Signal(Signal { body: None, .. }) => quote_cs! {},
             })
             .collect::<Vec<_>>();
 
@@ -122,6 +114,11 @@ impl<'ast> ClassContext<'ast> {
                 .map(|m| method(&m.sig, m.body, None)),
         );
 
+        ret.extend(self.interface_methods().map(|(iface, m)| {
+            let name = Self::interface_slot_name(&iface, &m.sig.name);
+            method(&m.sig, m.body, Some(Self::slot_impl_name(&name)))
+        }));
+
         return ret;
     }
 
@@ -131,9 +128,9 @@ impl<'ast> ClassContext<'ast> {
         let callback_guard = glib_callback_guard();
         let InstanceName = self.InstanceName;
         let InstanceNameFfi = self.InstanceNameFfi;
-        let tokens = |sig: &FnSig, parent_class: Option<Ident>| {
-            let trampoline_name = Self::slot_trampoline_name(&sig.name);
-            let method_impl_name = Self::slot_impl_name(&sig.name);
+        let tokens = |sig: &FnSig, slot_name: &Ident, parent_class: Option<Ident>| {
+            let trampoline_name = Self::slot_trampoline_name(slot_name);
+            let method_impl_name = Self::slot_impl_name(slot_name);
             let inputs = sig.input_args_with_glib_types();
             let arg_names = sig.input_args_from_glib_types();
 
@@ -167,9 +164,13 @@ impl<'ast> ClassContext<'ast> {
             .filter_map(|slot| match *slot {
                 Slot::Method(_) => None,
 
-                Slot::VirtualMethod(VirtualMethod { ref sig, .. }) => Some(tokens(sig, None)),
+                Slot::VirtualMethod(VirtualMethod { ref sig, .. }) => {
+                    Some(tokens(sig, &sig.name, None))
+                }
+
+                Slot::Signal(Signal { body: None, .. }) => None,
 
-                Slot::Signal(ref signal) => Some(tokens(&signal.sig, None)),
+                Slot::Signal(ref signal) => Some(tokens(&signal.sig, &signal.sig.name, None)),
             })
             .collect::<Vec<_>>();
 
@@ -180,10 +181,18 @@ impl<'ast> ClassContext<'ast> {
                 .flat_map(|(&p, methods)| methods.iter().map(move |m| (p, m)))
                 .map(|(parent_class, method)| {
                     // TODO: does the name here need mangling with the parent class?
-                    tokens(&method.sig, Some(parent_class))
+                    tokens(&method.sig, &method.sig.name, Some(parent_class))
                 }),
         );
 
+        // Interface methods are mangled with the interface's name, since
+        // the class or another interface may have a method with the
+        // same name
+        ret.extend(self.interface_methods().map(|(iface, method)| {
+            let slot_name = Self::interface_slot_name(&iface, &method.sig.name);
+            tokens(&method.sig, &slot_name, Some(iface))
+        }));
+
         return ret;
     }
 
@@ -253,6 +262,165 @@ impl<'ast> ClassContext<'ast> {
             .collect()
     }
 
+    /// Generates a `parent_foo()` function for each virtual method or
+    /// signal handler overridden in `impl Parent for Foo`, so that the
+    /// override can chain up to the parent's implementation.  It calls
+    /// the function in the parent class's slot, if any.
+    ///
+    /// Like in C, chaining up to an empty slot does nothing for
+    /// functions without a return value.  Signals don't need a default
+    /// handler, and a C parent class may leave a virtual method
+    /// unimplemented, so for functions with a return value
+    /// `parent_foo()` returns an `Option`, which is `None` if the slot
+    /// is empty.
+    pub fn imp_parent_chain_up_fns(&self) -> Vec<Tokens> {
+        let InstanceNameFfi = self.InstanceNameFfi;
+        self.class
+            .overrides
+            .iter()
+            .flat_map(|(&p, methods)| methods.iter().map(move |m| (p, m)))
+            .map(|(parent_class, method)| {
+                let sig = &method.sig;
+                let name = sig.name;
+                let parent_name = Self::parent_chain_up_name(&name);
+                let inputs = &sig.inputs;
+                let output = &sig.output;
+                let arg_names = sig.input_args_to_glib_types();
+                let value = quote_cs! {
+                    f(this as *mut _, #arg_names)
+                };
+                let ret = sig.ret_from_glib_fn(&value);
+
+                let (output, call, no_handler) = match *output {
+                    Ty::Unit => (quote_cs! { () }, quote_cs! { #ret }, quote_cs! { () }),
+                    // The slot is empty for signals without a default
+                    // handler, and for C classes that don't implement a
+                    // virtual method
+                    _ => (
+                        quote_cs! { Option<#output> },
+                        quote_cs! { Some(#ret) },
+                        quote_cs! { None },
+                    ),
+                };
+
+                quote_cs! {
+                    #[allow(dead_code)] // the override may not chain up
+                    fn #parent_name(#(#inputs),*) -> #output {
+                        unsafe {
+                            let this: *mut #InstanceNameFfi = self.to_glib_none().0;
+                            let klass = &*(PRIV.parent_class as *const <
+                                #parent_class as glib::wrapper::Wrapper
+                            >::GlibClassType);
+
+                            match klass.#name {
+                                Some(f) => #call,
+                                None => #no_handler,
+                            }
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the methods from all the `impl interface Iface for Foo`
+    /// blocks, along with the name of their interface.
+    fn interface_methods<'a>(&'a self) -> impl Iterator<Item = (Ident, &'a Override<'ast>)> + 'a {
+        self.class
+            .interface_impls
+            .iter()
+            .flat_map(|(&iface, methods)| methods.iter().map(move |m| (iface, m)))
+    }
+
+    /// Generates an `iface_interface_init()` function for each
+    /// interface that the class implements, which fills the interface's
+    /// vtable with trampolines to the class's implementations.  Slots
+    /// for methods that the class doesn't implement keep the
+    /// interface's default implementation.
+    pub fn interface_init_fns(&self) -> Vec<Tokens> {
+        let InstanceNameFfi = self.InstanceNameFfi;
+        let callback_guard = glib_callback_guard();
+        self.class
+            .interface_impls
+            .iter()
+            .map(|(iface, methods)| {
+                let init_name = Self::interface_init_name(iface);
+                let IfaceModule = iface.with_suffix("Mod");
+                let IfaceVtable = iface.with_suffix("Iface");
+                let assignments = methods
+                    .iter()
+                    .map(|method| {
+                        let name = method.sig.name;
+                        let slot_name = Self::interface_slot_name(iface, &name);
+                        let trampoline_name = Self::slot_trampoline_name(&slot_name);
+                        quote_cs! {
+                            iface.#name = Some(#InstanceNameFfi::#trampoline_name);
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                quote_cs! {
+                    unsafe extern "C" fn #init_name(iface: glib_ffi::gpointer, _iface_data: glib_ffi::gpointer) {
+                        #callback_guard
+
+                        #[allow(unused_variables)] // not used if no methods are implemented
+                        let iface = &mut *(iface as *mut super::super::#IfaceModule::imp::#IfaceVtable);
+                        #(#assignments)*
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the calls to `g_type_add_interface_static()` for the
+    /// class's interfaces, for its `get_type()` function.  They are in
+    /// the order of `class Foo: Parent, Iface1, Iface2`, which puts
+    /// prerequisites first.
+    pub fn interface_registrations(&self) -> Vec<Tokens> {
+        let ClassName = self.ClassName;
+        self.class
+            .implements
+            .iter()
+            .map(|iface| {
+                let init_name = Self::interface_init_name(iface);
+                quote_cs! {
+                    {
+                        let interface_info = gobject_ffi::GInterfaceInfo {
+                            interface_init: Some(#ClassName::#init_name),
+                            interface_finalize: None,
+                            interface_data: ptr::null_mut(),
+                        };
+
+                        gobject_ffi::g_type_add_interface_static(
+                            TYPE,
+                            <#iface as glib::StaticType>::static_type().to_glib(),
+                            &interface_info,
+                        );
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn interface_slot_name(iface: &Ident, slot_name: &Ident) -> Ident {
+        Ident::from(format!(
+            "{}_{}",
+            lower_case_instance_name(iface.as_ref()),
+            slot_name.as_ref()
+        ))
+    }
+
+    fn interface_init_name(iface: &Ident) -> Ident {
+        Ident::from(format!(
+            "{}_interface_init",
+            lower_case_instance_name(iface.as_ref())
+        ))
+    }
+
+    fn parent_chain_up_name(slot_name: &Ident) -> Ident {
+        Ident::from(format!("parent_{}", slot_name.as_ref()))
+    }
+
     fn slot_trampoline_name(slot_name: &Ident) -> Ident {
         Ident::from(format!("{}_slot_trampoline", slot_name.as_ref()))
     }
@@ -278,6 +446,8 @@ impl<'ast> ClassContext<'ast> {
                     })
                 }
 
+                Slot::Signal(Signal { body: None, .. }) => None,
+
                 Slot::Signal(ref signal) => {
                     let signalname = signal.sig.name;
                     let trampoline_name = Self::slot_trampoline_name(&signalname);
@@ -392,6 +562,7 @@ impl<'ast> ClassContext<'ast> {
 
         quote_cs! {
             #[repr(u32)]
+            #[allow(non_camel_case_types)] // the variants use the user's property names
             enum Properties {
                 #(#properties, )*
             }
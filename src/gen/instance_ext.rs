@@ -1,7 +1,11 @@
+// We give `ClassName` variables an identifier that uses upper-case.
+#![allow(non_snake_case)]
+
 use quote::Tokens;
 use syn::Ident;
 
-use hir::{Method, Slot, VirtualMethod};
+use glib_utils::*;
+use hir::{Method, Signal, Slot, VirtualMethod};
 
 use super::class::ClassContext;
 use super::signals;
@@ -18,6 +22,8 @@ impl<'ast> ClassContext<'ast> {
     /// ```notest
     /// fn connect_foo<F: Fn(&Self, arg: u32) + 'static>(&self, f: F) -> glib::SignalHandlerId
     /// ```
+    ///
+    /// plus the functions from `signal_trait_fns()`.
     pub fn slot_trait_fns(&self) -> Vec<Tokens> {
         self.class
             .slots
@@ -39,16 +45,7 @@ impl<'ast> ClassContext<'ast> {
                     })
                 }
 
-                Slot::Signal(ref signal) => {
-                    let connect_signalname = signals::connect_signalname(signal);
-                    let sig = &signal.sig;
-                    let input_types = signal.sig.input_arg_types();
-                    let output = &sig.output;
-                    Some(quote_cs! {
-                        fn #connect_signalname<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
-                            glib::SignalHandlerId;
-                    })
-                }
+                Slot::Signal(ref signal) => Some(self.signal_trait_fns(signal)),
             })
             .collect()
     }
@@ -72,49 +69,175 @@ impl<'ast> ClassContext<'ast> {
                     let ffi_name = self.method_ffi_name(name.as_ref());
                     let arg_names = sig.input_args_to_glib_types();
                     let value = quote_cs! {
-                        unsafe {
-                            imp::#ffi_name(self.to_glib_none().0,
-                                           #arg_names)
-                        }
+                        imp::#ffi_name(self.to_glib_none().0,
+                                       #arg_names)
                     };
                     let output_from = sig.ret_from_glib_fn(&value);
                     let inputs = &sig.inputs;
                     let output = &sig.output;
                     Some(quote_cs! {
                         fn #name(#(#inputs),*) -> #output {
-                            #output_from
-                        }
-                    })
-                }
-
-                Slot::Signal(ref signal) => {
-                    let connect_signalname = signals::connect_signalname(signal);
-                    let signalname_trampoline = signals::signal_trampoline_name(signal);
-                    let sig = &signal.sig;
-                    let signalname_str = sig.name.as_ref();
-                    let input_types = signal.sig.input_arg_types();
-                    let output = &sig.output;
-
-                    Some(quote_cs! {
-                        fn #connect_signalname<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
-                            glib::SignalHandlerId
-                        {
                             unsafe {
-                                let f: Box<Box<Fn(&Self, #input_types) -> #output + 'static>> =
-                                    Box::new(Box::new(f));
-
-                                glib::signal::connect(self.to_glib_none().0,
-                                                      #signalname_str,
-                                                      mem::transmute(#signalname_trampoline::<Self> as usize),
-                                                      Box::into_raw(f) as *mut _)
+                                #output_from
                             }
                         }
                     })
                 }
+
+                Slot::Signal(ref signal) => Some(self.signal_trait_impls(signal)),
             })
             .collect()
     }
 
+    /// Returns the trait functions for a signal:  `connect_foo()`,
+    /// `connect_foo_after()`, `block_foo()` and `unblock_foo()` for the
+    /// handlers returned by those, `stop_emission_foo()`, and
+    /// `connect_foo_detailed()` if the signal is `#[detailed]`.
+    fn signal_trait_fns(&self, signal: &Signal) -> Tokens {
+        let connect_signalname = signals::connect_signalname(signal);
+        let connect_signalname_after = signals::connect_signalname_after(signal);
+        let block_signalname = signals::block_signalname(signal);
+        let unblock_signalname = signals::unblock_signalname(signal);
+        let stop_emission_signalname = signals::stop_emission_signalname(signal);
+        let input_types = signal.sig.input_arg_types();
+        let output = &signal.sig.output;
+
+        let connect_detailed = if signal.flags.detailed {
+            let connect_signalname_detailed = signals::connect_signalname_detailed(signal);
+            quote_cs! {
+                fn #connect_signalname_detailed<F: Fn(&Self, #input_types) -> #output + 'static>(&self, detail: &str, f: F) ->
+                    glib::SignalHandlerId;
+            }
+        } else {
+            quote_cs! {}
+        };
+
+        quote_cs! {
+            fn #connect_signalname<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
+                glib::SignalHandlerId;
+
+            fn #connect_signalname_after<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
+                glib::SignalHandlerId;
+
+            #connect_detailed
+
+            fn #block_signalname(&self, handler_id: &glib::SignalHandlerId);
+
+            fn #unblock_signalname(&self, handler_id: &glib::SignalHandlerId);
+
+            fn #stop_emission_signalname(&self);
+        }
+    }
+
+    /// Generates the implementations of the functions from
+    /// `signal_trait_fns()`.
+    fn signal_trait_impls(&self, signal: &Signal) -> Tokens {
+        let InstanceName = self.InstanceName;
+        let connect_signalname = signals::connect_signalname(signal);
+        let connect_signalname_after = signals::connect_signalname_after(signal);
+        let block_signalname = signals::block_signalname(signal);
+        let unblock_signalname = signals::unblock_signalname(signal);
+        let stop_emission_signalname = signals::stop_emission_signalname(signal);
+        let signalname_trampoline = signals::signal_trampoline_name(signal);
+        let signal_id_fn_name = signals::signal_id_fn_name(signal);
+        let signalname_str = signal.sig.name.as_ref();
+        let input_types = signal.sig.input_arg_types();
+        let output = &signal.sig.output;
+        let callback_guard = glib_callback_guard();
+
+        // Connecting to "foo::detail" makes GObject quark the detail
+        let connect_detailed = if signal.flags.detailed {
+            let connect_signalname_detailed = signals::connect_signalname_detailed(signal);
+            quote_cs! {
+                fn #connect_signalname_detailed<F: Fn(&Self, #input_types) -> #output + 'static>(&self, detail: &str, f: F) ->
+                    glib::SignalHandlerId
+                {
+                    unsafe {
+                        let f: Box<Box<Fn(&Self, #input_types) -> #output + 'static>> =
+                            Box::new(Box::new(f));
+
+                        glib::signal::connect(self.to_glib_none().0,
+                                              &format!("{}::{}", #signalname_str, detail),
+                                              mem::transmute(#signalname_trampoline::<Self> as usize),
+                                              Box::into_raw(f) as *mut _)
+                    }
+                }
+            }
+        } else {
+            quote_cs! {}
+        };
+
+        quote_cs! {
+            fn #connect_signalname<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
+                glib::SignalHandlerId
+            {
+                unsafe {
+                    let f: Box<Box<Fn(&Self, #input_types) -> #output + 'static>> =
+                        Box::new(Box::new(f));
+
+                    glib::signal::connect(self.to_glib_none().0,
+                                          #signalname_str,
+                                          mem::transmute(#signalname_trampoline::<Self> as usize),
+                                          Box::into_raw(f) as *mut _)
+                }
+            }
+
+            fn #connect_signalname_after<F: Fn(&Self, #input_types) -> #output + 'static>(&self, f: F) ->
+                glib::SignalHandlerId
+            {
+                // glib::signal::connect() doesn't take connect flags, so
+                // we need our own version of its destroy notify
+                unsafe extern "C" fn destroy_closure(ptr: glib_ffi::gpointer, _: *mut gobject_ffi::GClosure) {
+                    #callback_guard
+
+                    drop(Box::<Box<Fn() + 'static>>::from_raw(ptr as *mut _));
+                }
+
+                unsafe {
+                    let f: Box<Box<Fn(&Self, #input_types) -> #output + 'static>> =
+                        Box::new(Box::new(f));
+
+                    let handler_id = gobject_ffi::g_signal_connect_data(
+                        self.to_glib_none().0,
+                        #signalname_str.to_glib_none().0,
+                        Some(mem::transmute(#signalname_trampoline::<Self> as usize)),
+                        Box::into_raw(f) as *mut _,
+                        Some(destroy_closure),
+                        gobject_ffi::G_CONNECT_AFTER,
+                    );
+                    from_glib(handler_id)
+                }
+            }
+
+            #connect_detailed
+
+            fn #block_signalname(&self, handler_id: &glib::SignalHandlerId) {
+                glib::signal::signal_handler_block(self, handler_id);
+            }
+
+            fn #unblock_signalname(&self, handler_id: &glib::SignalHandlerId) {
+                glib::signal::signal_handler_unblock(self, handler_id);
+            }
+
+            fn #stop_emission_signalname(&self) {
+                unsafe {
+                    let signal_id = #InstanceName::#signal_id_fn_name();
+
+                    // g_signal_stop_emission() needs the detail of the
+                    // emission to stop, which we get from its invocation hint
+                    let hint = gobject_ffi::g_signal_get_invocation_hint(self.to_glib_none().0);
+                    let detail = if !hint.is_null() && (*hint).signal_id == signal_id {
+                        (*hint).detail
+                    } else {
+                        0
+                    };
+
+                    gobject_ffi::g_signal_stop_emission(self.to_glib_none().0, signal_id, detail);
+                }
+            }
+        }
+    }
+
     pub fn method_ffi_name(&self, method: &str) -> Ident {
         self.exported_fn_name(method)
     }
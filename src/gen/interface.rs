@@ -1,21 +1,38 @@
 // We give `ClassName` variables an identifier that uses upper-case.
 #![allow(non_snake_case)]
 
+use proc_macro2::Span;
 use quote::Tokens;
+use syn::Ident;
 
-use gen::WithSuffix;
-use hir::{Interface, Program};
+use glib_utils::*;
+
+use gen::{g_critical, WithSuffix};
+use hir::{Interface, Program, Slot, VirtualMethod};
+
+use super::cstringident::CStringIdent;
 
 pub struct InterfaceContext<'ast> {
     pub program: &'ast Program<'ast>,
     pub iface: &'ast Interface<'ast>,
+    pub ModuleName: Ident,
+    pub InterfaceName: &'ast Ident,
+    pub InterfaceNameFfi: Ident,
+    pub InterfaceNameIface: Ident,
+    pub InterfaceExt: Ident,
 }
 
 impl<'ast> InterfaceContext<'ast> {
+    #[cfg_attr(rustfmt, rustfmt_skip)]
     pub fn new(program: &'ast Program, iface: &'ast Interface) -> Self {
         InterfaceContext {
             program,
             iface,
+            ModuleName: iface.name.with_suffix("Mod"), // toplevel "InterfaceMod" module name
+            InterfaceName: &iface.name,
+            InterfaceNameFfi: iface.name.with_suffix("Ffi"), // opaque instance type
+            InterfaceNameIface: iface.name.with_suffix("Iface"), // vtable struct
+            InterfaceExt: iface.name.with_suffix("Ext"), // public trait with all the methods
         }
     }
 
@@ -24,12 +41,34 @@ impl<'ast> InterfaceContext<'ast> {
     }
 
     fn gen_boilerplate(&self) -> Tokens {
-        let ModuleName = self.iface.name.with_suffix("Mod");
-        let Name = &self.iface.name;
-        let NameIface = self.iface.name.with_suffix("Iface");
+        let ModuleName = &self.ModuleName;
+        let InterfaceName = self.InterfaceName;
+        let InterfaceNameFfi = &self.InterfaceNameFfi;
+        let InterfaceNameIface = &self.InterfaceNameIface;
+        let InterfaceExt = &self.InterfaceExt;
+
+        let callback_guard = glib_callback_guard();
+        let get_type_fn_name = self.get_type_fn_name();
+        let interface_name_string = CStringIdent(*InterfaceName);
+
+        let slots = self.slots();
+        let slot_default_handlers = self.slot_default_handlers();
+        let slot_trampolines = self.slot_trampolines();
+        let slot_assignments = self.slot_assignments();
+        let extern_methods = self.extern_methods();
+        let slot_trait_fns = self.slot_trait_fns();
+        let slot_trait_impls = self.slot_trait_impls();
+
+        // The wrapper is IsA<> its prerequisites, so that their methods
+        // can be called on it
+        let prerequisites = &self.iface.prerequisites;
+        let prerequisite_tokens = if prerequisites.is_empty() {
+            quote_cs! {}
+        } else {
+            quote_cs! { : #(#prerequisites),* }
+        };
+        let prerequisite_registrations = self.prerequisite_registrations();
 
-        let slots = quote_cs! {}; // FIXME
-        
         quote_cs! {
             pub mod #ModuleName {
                 #![allow(non_snake_case)] // "oddly" named module above
@@ -37,25 +76,337 @@ impl<'ast> InterfaceContext<'ast> {
                 extern crate glib_sys as glib_ffi;
                 extern crate gobject_sys as gobject_ffi;
 
+                extern crate glib;
                 extern crate libc;
 
+                use glib::IsA;
+                use glib::translate::*;
+                use std::mem;
+                use std::ptr;
+
+                // Bring in our parent's stuff, so that the types in the
+                // method signatures are in scope
+                #[allow(unused_imports)]
+                use super::*;
+
+                glib_wrapper! {
+                    pub struct #InterfaceName(Object<imp::#InterfaceNameFfi>)
+                        #prerequisite_tokens;
+
+                    match fn {
+                        get_type => || imp::#get_type_fn_name(),
+                    }
+                }
+
                 pub mod imp {
+                    #[allow(unused_imports)]
+                    use super::super::*;
+
+                    use super::glib;
                     use super::glib_ffi;
                     use super::gobject_ffi;
-
                     use super::libc;
 
+                    use std::mem;
+
+                    #[allow(unused_imports)]
+                    use glib::translate::*;
+
+                    // Instances of the interface are opaque; they are
+                    // really instances of the classes that implement it
                     #[repr(C)]
-                    pub struct #Name(libc::c_void);
+                    pub struct #InterfaceNameFfi(libc::c_void);
 
                     #[repr(C)]
-                    pub struct #NameIface {
+                    pub struct #InterfaceNameIface {
                         pub parent_iface: gobject_ffi::GTypeInterface,
-
                         #(#slots)*
                     }
+
+                    // The default implementations of methods, as in
+                    // `impl super::Foo` for classes
+                    impl super::#InterfaceName {
+                        #(#slot_default_handlers)*
+                    }
+
+                    impl #InterfaceNameFfi {
+                        #(#slot_trampolines)*
+                    }
+
+                    impl #InterfaceNameIface {
+                        // Fills in the default vtable, which classes copy
+                        // before their own interface init function runs
+                        unsafe extern "C" fn default_init(iface: glib_ffi::gpointer, _iface_data: glib_ffi::gpointer) {
+                            #callback_guard
+
+                            #[allow(unused_variables)] // not used if no default implementations
+                            let iface = &mut *(iface as *mut #InterfaceNameIface);
+                            #(#slot_assignments)*
+                        }
+                    }
+
+                    #[allow(dead_code)] // not used if no methods
+                    unsafe fn get_iface(this: *mut #InterfaceNameFfi) -> &'static #InterfaceNameIface {
+                        let klass = (*(this as *const gobject_ffi::GTypeInstance)).g_class;
+                        let iface = gobject_ffi::g_type_interface_peek(klass as glib_ffi::gpointer, #get_type_fn_name());
+                        &*(iface as *const #InterfaceNameIface)
+                    }
+
+                    #(#extern_methods)*
+
+                    #[no_mangle]
+                    pub unsafe extern "C" fn #get_type_fn_name() -> glib_ffi::GType {
+                        #callback_guard
+
+                        use std::sync::{Once, ONCE_INIT};
+                        use std::u16;
+
+                        static mut TYPE: glib_ffi::GType = gobject_ffi::G_TYPE_INVALID;
+                        static ONCE: Once = ONCE_INIT;
+
+                        ONCE.call_once(|| {
+                            let iface_size = mem::size_of::<#InterfaceNameIface>();
+                            assert!(iface_size <= u16::MAX as usize);
+
+                            TYPE = gobject_ffi::g_type_register_static_simple(
+                                gobject_ffi::G_TYPE_INTERFACE,
+                                #interface_name_string as *const u8 as *const i8,
+                                iface_size as u32,
+                                Some(#InterfaceNameIface::default_init),
+                                0,
+                                None,
+                                gobject_ffi::GTypeFlags::empty()
+                            );
+
+                            #(#prerequisite_registrations)*
+                        });
+
+                        TYPE
+                    }
+                }
+
+                pub trait #InterfaceExt {
+                    #(#slot_trait_fns)*
+                }
+
+                impl<O: IsA<#InterfaceName> + IsA<glib::object::Object>> #InterfaceExt for O {
+                    #(#slot_trait_impls)*
                 }
             }
+
+            pub use self::#ModuleName::*;
+        }
+    }
+
+    /// Returns the calls to `g_type_interface_add_prerequisite()`.
+    /// Without explicit prerequisites, the interface requires GObject;
+    /// GObject doesn't allow adding it as well as a class prerequisite.
+    fn prerequisite_registrations(&self) -> Vec<Tokens> {
+        let add_prerequisite = |gtype: Tokens| {
+            quote_cs! {
+                gobject_ffi::g_type_interface_add_prerequisite(TYPE, #gtype);
+            }
+        };
+
+        if self.iface.prerequisites.is_empty() {
+            vec![add_prerequisite(quote_cs! { gobject_ffi::g_object_get_type() })]
+        } else {
+            self.iface
+                .prerequisites
+                .iter()
+                .map(|path| {
+                    add_prerequisite(quote_cs! {
+                        <#path as glib::StaticType>::static_type().to_glib()
+                    })
+                })
+                .collect()
         }
     }
+
+    fn virtual_methods(&self) -> impl Iterator<Item = &'ast VirtualMethod<'ast>> {
+        self.iface.slots.iter().filter_map(|slot| match *slot {
+            Slot::VirtualMethod(ref method) => Some(method),
+            _ => None,
+        })
+    }
+
+    /// Returns the fields of the `FooIface` vtable, one function
+    /// pointer per virtual method.  This defines the C ABI for the
+    /// interface.
+    fn slots(&self) -> Vec<Tokens> {
+        let InterfaceNameFfi = &self.InterfaceNameFfi;
+        self.virtual_methods()
+            .map(|method| {
+                let name = method.sig.name;
+                let inputs = method.sig.input_args_with_glib_types();
+                let output = method.sig.output_glib_type();
+                quote_cs! {
+                    pub #name: Option<unsafe extern "C" fn(
+                        this: *mut #InterfaceNameFfi,
+                        #inputs
+                    ) -> #output>,
+                }
+            })
+            .collect()
+    }
+
+    fn slot_default_handlers(&self) -> Vec<Tokens> {
+        self.virtual_methods()
+            .filter_map(|method| {
+                method.body.map(|body| {
+                    let name = slot_impl_name(&method.sig.name);
+                    let inputs = &method.sig.inputs;
+                    let output = &method.sig.output;
+                    quote_cs! {
+                        fn #name(#(#inputs),*) -> #output #body
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn slot_trampolines(&self) -> Vec<Tokens> {
+        let InterfaceName = self.InterfaceName;
+        let InterfaceNameFfi = &self.InterfaceNameFfi;
+        let callback_guard = glib_callback_guard();
+        self.virtual_methods()
+            .filter(|method| method.body.is_some())
+            .map(|method| {
+                let sig = &method.sig;
+                let trampoline_name = slot_trampoline_name(&sig.name);
+                let method_impl_name = slot_impl_name(&sig.name);
+                let inputs = sig.input_args_with_glib_types();
+                let arg_names = sig.input_args_from_glib_types();
+                let ret = quote_cs! { instance.#method_impl_name(#arg_names) };
+                let ret = sig.ret_to_glib(ret);
+                let output = sig.output_glib_type();
+                quote_cs! {
+                    unsafe extern "C" fn #trampoline_name(
+                        this: *mut #InterfaceNameFfi,
+                        #inputs
+                    )
+                        -> #output
+                    {
+                        #callback_guard
+
+                        let instance: &super::#InterfaceName = &from_glib_borrow(this);
+                        #ret
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Methods without a default implementation leave their vtable slot
+    /// empty, for the implementing classes to fill in.
+    fn slot_assignments(&self) -> Vec<Tokens> {
+        let InterfaceNameFfi = &self.InterfaceNameFfi;
+        self.virtual_methods()
+            .filter(|method| method.body.is_some())
+            .map(|method| {
+                let name = method.sig.name;
+                let trampoline_name = slot_trampoline_name(&name);
+                quote_cs! {
+                    iface.#name = Some(#InterfaceNameFfi::#trampoline_name);
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the exported C functions, like `foo_bar()`, which
+    /// dispatch through the instance's vtable for the interface.
+    fn extern_methods(&self) -> Vec<Tokens> {
+        let InterfaceNameFfi = &self.InterfaceNameFfi;
+        let callback_guard = glib_callback_guard();
+        self.virtual_methods()
+            .map(|method| {
+                let sig = &method.sig;
+                let name = sig.name;
+                let ffi_name = self.method_ffi_name(name.as_ref());
+                let inputs = sig.input_args_with_glib_types();
+                let args = sig.input_arg_names();
+                let output = sig.output_glib_type();
+                // Like g_return_val_if_fail(), since C callers can get here
+                let critical = g_critical(&format!("no implementation of {}", ffi_name), quote_cs!{});
+                quote_cs! {
+                    #[no_mangle]
+                    pub unsafe extern "C" fn #ffi_name(this: *mut #InterfaceNameFfi,
+                                                       #inputs)
+                        -> #output
+                    {
+                        #callback_guard
+
+                        match get_iface(this).#name {
+                            Some(f) => f(this, #args),
+                            None => {
+                                #critical;
+                                ::std::mem::zeroed()
+                            }
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn slot_trait_fns(&self) -> Vec<Tokens> {
+        self.virtual_methods()
+            .map(|method| {
+                let name = method.sig.name;
+                let inputs = &method.sig.inputs;
+                let output = &method.sig.output;
+                quote_cs! {
+                    fn #name(#(#inputs),*) -> #output;
+                }
+            })
+            .collect()
+    }
+
+    fn slot_trait_impls(&self) -> Vec<Tokens> {
+        self.virtual_methods()
+            .map(|method| {
+                let sig = &method.sig;
+                let name = sig.name;
+                let ffi_name = self.method_ffi_name(name.as_ref());
+                let arg_names = sig.input_args_to_glib_types();
+                let value = quote_cs! {
+                    imp::#ffi_name(self.to_glib_none().0,
+                                   #arg_names)
+                };
+                let output_from = sig.ret_from_glib_fn(&value);
+                let inputs = &sig.inputs;
+                let output = &sig.output;
+                quote_cs! {
+                    fn #name(#(#inputs),*) -> #output {
+                        unsafe {
+                            #output_from
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn method_ffi_name(&self, method: &str) -> Ident {
+        Ident::new(
+            &format!(
+                "{}_{}",
+                lower_case_instance_name(self.InterfaceName.as_ref()),
+                method
+            ),
+            Span::call_site(),
+        )
+    }
+
+    fn get_type_fn_name(&self) -> Ident {
+        self.method_ffi_name("get_type")
+    }
+}
+
+fn slot_trampoline_name(slot_name: &Ident) -> Ident {
+    Ident::from(format!("{}_slot_trampoline", slot_name.as_ref()))
+}
+
+fn slot_impl_name(slot_name: &Ident) -> Ident {
+    Ident::from(format!("{}_impl", slot_name.as_ref()))
 }
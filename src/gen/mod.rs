@@ -2,21 +2,29 @@
 #![allow(non_snake_case)]
 
 use proc_macro2::Span;
-use quote::Tokens;
+use quote::{ToTokens, Tokens};
 use syn::Ident;
 
 mod boilerplate;
+mod boxed;
 mod class;
+mod constructor;
 mod cstringident;
+mod enums;
 mod imp;
 mod interface;
 mod instance_ext;
+mod properties;
 mod signals;
 mod signatures;
+mod values;
 
+use self::boxed::BoxedContext;
 use self::class::ClassContext;
+use self::cstringident::CStringLiteral;
+use self::enums::EnumContext;
 use self::interface::InterfaceContext;
-use hir::Program;
+use hir::{FnSig, Program, Slot, Ty};
 
 pub fn codegen(program: &Program) -> Tokens {
     let class_tokens = program
@@ -37,13 +45,103 @@ pub fn codegen(program: &Program) -> Tokens {
         })
         .collect::<Vec<_>>();
 
+    let enum_tokens = program
+        .types
+        .enums
+        .iter()
+        .map(|enum_| {
+            let cx = EnumContext::new(program, enum_);
+            cx.gen_enum()
+        })
+        .collect::<Vec<_>>();
+
+    let boxed_tokens = program
+        .types
+        .boxed
+        .iter()
+        .map(|boxed| {
+            let cx = BoxedContext::new(program, boxed);
+            cx.gen_boxed()
+        })
+        .collect::<Vec<_>>();
+
+    // isize and usize are passed as C longs, which are narrower on
+    // LLP64 targets like 64-bit Windows
+    let long_integers_check = if uses_long_integers(program) {
+        quote_cs! {
+            #[cfg(all(windows, not(target_pointer_width = "32")))]
+            compile_error!("isize and usize are passed as glong and gulong, which are narrower on this target");
+        }
+    } else {
+        quote_cs! {}
+    };
+
     quote_cs! {
+        #long_integers_check
+
+        #(#enum_tokens)*
+
+        #(#boxed_tokens)*
+
         #(#class_tokens)*
 
         #(#interface_tokens)*
     }
 }
 
+/// Returns whether any signature or property in the program uses
+/// `isize` or `usize`.
+fn uses_long_integers(program: &Program) -> bool {
+    fn is_long(ty: &Ty) -> bool {
+        match *ty {
+            Ty::Integer(ref i) => i.as_ref() == "isize" || i.as_ref() == "usize",
+            Ty::Borrowed(ref inner) | Ty::Option(ref inner) => is_long(inner),
+            _ => false,
+        }
+    }
+
+    fn sig_is_long(sig: &FnSig) -> bool {
+        is_long(&sig.output) || sig.arg_types().any(is_long)
+    }
+
+    fn slot_is_long(slot: &Slot) -> bool {
+        match *slot {
+            Slot::Method(ref m) => sig_is_long(&m.sig),
+            Slot::VirtualMethod(ref m) => sig_is_long(&m.sig),
+            Slot::Signal(ref s) => sig_is_long(&s.sig),
+        }
+    }
+
+    program.classes.iter().any(|class| {
+        class.slots.iter().any(slot_is_long)
+            || class.properties.iter().any(|prop| is_long(&prop.type_))
+            || class
+                .overrides
+                .values()
+                .chain(class.interface_impls.values())
+                .flat_map(|overrides| overrides.iter())
+                .any(|o| sig_is_long(&o.sig))
+    }) || program
+        .interfaces
+        .iter()
+        .any(|iface| iface.slots.iter().any(slot_is_long))
+}
+
+/// Generates a call that logs a critical warning, like `g_critical()`
+/// in C.  Code that gets invalid values from C uses this instead of
+/// panicking, since a panic can't unwind across the C stack.
+fn g_critical<T: ToTokens>(format: &str, args: T) -> Tokens {
+    let format = CStringLiteral(format);
+    quote_cs! {
+        glib_ffi::g_log(
+            ::std::ptr::null(),
+            glib_ffi::G_LOG_LEVEL_CRITICAL,
+            #format as *const u8 as *const i8,
+            #args
+        )
+    }
+}
+
 trait WithSuffix: AsRef<str> {
     fn with_suffix(&self, suffix: &str) -> Ident {
         Ident::new(
@@ -0,0 +1,703 @@
+use quote::Tokens;
+use syn::{self, Ident};
+
+use glib_utils::*;
+use hir::{Property, Ty};
+use param::canonical_param_name;
+
+use super::class::ClassContext;
+use super::cstringident::CStringLiteral;
+use super::g_critical;
+use super::signatures::promoted_integer_type;
+use super::values;
+
+impl<'ast> ClassContext<'ast> {
+    /// Generates the `impl super::Foo` functions with the bodies of
+    /// the user's `get` and `set` blocks.
+    pub fn property_default_handlers(&self) -> Vec<Tokens> {
+        self.class
+            .properties
+            .iter()
+            .map(|prop| {
+                let type_ = &prop.type_;
+
+                let getter = prop.getter.map(|body| {
+                    let getter_name = property_getter_impl_name(prop);
+                    quote_cs! {
+                        fn #getter_name(&self) -> #type_ #body
+                    }
+                });
+
+                let setter = prop.setter.as_ref().map(|setter| {
+                    let setter_name = property_setter_impl_name(prop);
+                    let param = setter.param;
+                    let body = setter.body;
+                    quote_cs! {
+                        fn #setter_name(&self, #param: #type_) #body
+                    }
+                });
+
+                quote_cs! {
+                    #getter
+
+                    #setter
+                }
+            })
+            .collect()
+    }
+
+    /// Generates a `Foo::pspec_foo()` function for each property.  It
+    /// returns the property's `GParamSpec` as stored in
+    /// `PRIV.properties` by `class_init()`.
+    pub fn property_pspec_fns(&self) -> Vec<Tokens> {
+        let get_type_fn_name = self.instance_get_type_fn_name();
+
+        self.class
+            .properties
+            .iter()
+            .map(|prop| {
+                let name = prop.name;
+                let pspec_name = property_pspec_name(prop);
+
+                quote_cs! {
+                    pub fn #pspec_name() -> *mut gobject_ffi::GParamSpec {
+                        unsafe {
+                            // Make sure that class_init() has run.  Our type is
+                            // static, so its class doesn't go away when unreffed.
+                            let klass = gobject_ffi::g_type_class_ref(#get_type_fn_name());
+                            gobject_ffi::g_type_class_unref(klass);
+
+                            (&*PRIV.properties)[Properties::#name as usize]
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates a `notify_foo()` function for each property, for use
+    /// by the class's implementation.  This emits "notify" with the
+    /// property's `GParamSpec` from `pspec_foo()`, so there is no
+    /// lookup by name.  Properties with `#[explicit_notify]` depend on
+    /// this, since GObject doesn't notify them automatically when they
+    /// are set.
+    pub fn property_notify_fns(&self) -> Vec<Tokens> {
+        let InstanceNameFfi = &self.InstanceNameFfi;
+
+        self.class
+            .properties
+            .iter()
+            .map(|prop| {
+                let notify_name = property_notify_name(prop);
+                let pspec_name = property_pspec_name(prop);
+
+                quote_cs! {
+                    #[allow(dead_code)]
+                    fn #notify_name(&self) {
+                        unsafe {
+                            let obj: *mut #InstanceNameFfi = self.to_glib_none().0;
+
+                            gobject_ffi::g_object_notify_by_pspec(
+                                obj as *mut gobject_ffi::GObject,
+                                Self::#pspec_name(),
+                            );
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the `set_property()` and `get_property()`
+    /// implementations for `GObjectClass`.  These dispatch property
+    /// ids to the handlers from `property_default_handlers()`.
+    pub fn property_vfuncs(&self) -> Tokens {
+        if self.class.properties.len() == 0 {
+            return quote_cs!{};
+        }
+
+        let InstanceName = self.InstanceName;
+        let InstanceNameFfi = &self.InstanceNameFfi;
+        let callback_guard = glib_callback_guard();
+        let warn_invalid_property_id = warn_invalid_property_id();
+
+        let setters = self.writable_properties().map(|prop| {
+            let name = prop.name;
+            let setter_name = property_setter_impl_name(prop);
+
+            // GObject accepts NULL for any boxed property, but the
+            // setter takes the value itself
+            let set = match prop.type_ {
+                Ty::Boxed(ref t) => {
+                    let critical = g_critical(
+                        "can't set boxed property \"%s\" to NULL",
+                        quote_cs! { (*pspec).name },
+                    );
+                    quote_cs! {
+                        match value.get::<#t>() {
+                            Some(v) => instance.#setter_name(v),
+                            None => #critical,
+                        }
+                    }
+                }
+                _ => {
+                    let value_get = values::from_value(&prop.type_, &quote_cs! { value });
+                    quote_cs! { instance.#setter_name(#value_get); }
+                }
+            };
+
+            quote_cs! {
+                id if id == Properties::#name as u32 => {
+                    let value = &*(value as *const glib::Value);
+                    #set
+                }
+            }
+        });
+
+        let getters = self.readable_properties().map(|prop| {
+            let name = prop.name;
+            let getter_name = property_getter_impl_name(prop);
+            let v = values::to_value(&prop.type_, &quote_cs! { instance.#getter_name() });
+
+            quote_cs! {
+                id if id == Properties::#name as u32 => {
+                    let v = #v;
+                    gobject_ffi::g_value_copy(v.to_glib_none().0, value);
+                }
+            }
+        });
+
+        quote_cs! {
+            unsafe extern "C" fn set_property(
+                obj: *mut gobject_ffi::GObject,
+                property_id: u32,
+                value: *mut gobject_ffi::GValue,
+                pspec: *mut gobject_ffi::GParamSpec,
+            ) {
+                #callback_guard
+
+                let instance: &super::#InstanceName = &from_glib_borrow(obj as *mut #InstanceNameFfi);
+
+                match property_id {
+                    #(#setters)*
+
+                    _ => { #warn_invalid_property_id }
+                }
+            }
+
+            unsafe extern "C" fn get_property(
+                obj: *mut gobject_ffi::GObject,
+                property_id: u32,
+                value: *mut gobject_ffi::GValue,
+                pspec: *mut gobject_ffi::GParamSpec,
+            ) {
+                #callback_guard
+
+                let instance: &super::#InstanceName = &from_glib_borrow(obj as *mut #InstanceNameFfi);
+
+                match property_id {
+                    #(#getters)*
+
+                    _ => { #warn_invalid_property_id }
+                }
+            }
+        }
+    }
+
+    /// Generates the code in `class_init()` that hooks up the
+    /// property vfuncs and installs a `GParamSpec` for each property.
+    ///
+    /// This expects a `gobject_class: &mut GObjectClass` in scope.
+    pub fn properties_registration(&self) -> Tokens {
+        if self.class.properties.len() == 0 {
+            return quote_cs!{};
+        }
+
+        let InstanceNameFfi = &self.InstanceNameFfi;
+        let param_specs = self.class.properties.iter().map(param_spec);
+
+        quote_cs! {
+            gobject_class.set_property = Some(#InstanceNameFfi::set_property);
+            gobject_class.get_property = Some(#InstanceNameFfi::get_property);
+
+            // Property id 0 is reserved by GObject, so that slot must be NULL
+            let mut properties: Vec<*mut gobject_ffi::GParamSpec> = vec![ptr::null_mut()];
+            #(properties.push(#param_specs);)*
+
+            gobject_ffi::g_object_class_install_properties(
+                gobject_class,
+                properties.len() as u32,
+                properties.as_mut_ptr(),
+            );
+
+            // The class owns the param specs now; keep the pointers
+            // around so that we can emit notifications without looking
+            // up properties by name.
+            PRIV.properties = Box::into_raw(Box::new(properties));
+        }
+    }
+
+    /// Returns, for each property, something like
+    ///
+    /// ```notest
+    /// fn get_foo(&self) -> u32;
+    /// fn set_foo(&self, v: u32);
+    /// fn connect_foo_notify<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId;
+    /// ```
+    pub fn property_trait_fns(&self) -> Vec<Tokens> {
+        self.class
+            .properties
+            .iter()
+            .map(|prop| {
+                let get_name = property_get_name(prop);
+                let set_name = property_set_name(prop);
+                let connect_notify_name = property_connect_notify_name(prop);
+                let type_ = &prop.type_;
+
+                let getter = if prop.is_readable() {
+                    Some(quote_cs! {
+                        fn #get_name(&self) -> #type_;
+                    })
+                } else {
+                    None
+                };
+
+                let setter = if is_settable(prop) {
+                    Some(quote_cs! {
+                        fn #set_name(&self, v: #type_);
+                    })
+                } else {
+                    None
+                };
+
+                quote_cs! {
+                    #getter
+
+                    #setter
+
+                    fn #connect_notify_name<F: Fn(&Self) + 'static>(&self, f: F) ->
+                        glib::SignalHandlerId;
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the implementations of the trait functions defined
+    /// in `property_trait_fns()`.
+    ///
+    /// These go through `g_object_get_property()` and
+    /// `g_object_set_property()` instead of calling the user's `get`
+    /// and `set` blocks directly, so that GObject can validate the
+    /// values and emit notifications as usual.
+    pub fn property_trait_impls(&self) -> Vec<Tokens> {
+        let InstanceName = self.InstanceName;
+
+        self.class
+            .properties
+            .iter()
+            .map(|prop| {
+                let get_name = property_get_name(prop);
+                let set_name = property_set_name(prop);
+                let connect_notify_name = property_connect_notify_name(prop);
+                let type_ = &prop.type_;
+                let name = canonical_param_name(prop.name.as_ref());
+                let detailed_notify = format!("notify::{}", name);
+                let name = CStringLiteral(&name);
+                let value_get = values::from_value(type_, &quote_cs! { value });
+                let v = values::to_value(type_, &quote_cs! { v });
+                let pspec_name = property_pspec_name(prop);
+
+                let getter = if prop.is_readable() {
+                    Some(quote_cs! {
+                        fn #get_name(&self) -> #type_ {
+                            unsafe {
+                                let pspec = #InstanceName::#pspec_name();
+                                let mut value = glib::Value::from_type(from_glib((*pspec).value_type));
+                                gobject_ffi::g_object_get_property(
+                                    self.to_glib_none().0,
+                                    (*pspec).name,
+                                    value.to_glib_none_mut().0,
+                                );
+                                #value_get
+                            }
+                        }
+                    })
+                } else {
+                    None
+                };
+
+                let setter = if is_settable(prop) {
+                    Some(quote_cs! {
+                        fn #set_name(&self, v: #type_) {
+                            let value = #v;
+                            unsafe {
+                                gobject_ffi::g_object_set_property(
+                                    self.to_glib_none().0,
+                                    #name as *const u8 as *const i8,
+                                    value.to_glib_none().0,
+                                );
+                            }
+                        }
+                    })
+                } else {
+                    None
+                };
+
+                quote_cs! {
+                    #getter
+
+                    #setter
+
+                    fn #connect_notify_name<F: Fn(&Self) + 'static>(&self, f: F) ->
+                        glib::SignalHandlerId
+                    {
+                        unsafe {
+                            let f: Box<Box<Fn(&Self) + 'static>> = Box::new(Box::new(f));
+
+                            glib::signal::connect(self.to_glib_none().0,
+                                                  #detailed_notify,
+                                                  mem::transmute(property_notify_trampoline::<Self> as usize),
+                                                  Box::into_raw(f) as *mut _)
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn readable_properties(&self) -> impl Iterator<Item = &Property> {
+        self.class.properties.iter().filter(|prop| prop.is_readable())
+    }
+
+    fn writable_properties(&self) -> impl Iterator<Item = &Property> {
+        self.class.properties.iter().filter(|prop| prop.is_writable())
+    }
+
+    /// Generates the trampoline that `connect_foo_notify()` passes to
+    /// `g_signal_connect()` for the "notify" signal.
+    pub fn property_notify_trampoline(&self) -> Tokens {
+        if self.class.properties.len() == 0 {
+            return quote_cs!{};
+        }
+
+        let InstanceName = self.InstanceName;
+        let InstanceNameFfi = &self.InstanceNameFfi;
+        let callback_guard = glib_callback_guard();
+
+        quote_cs! {
+            unsafe extern "C" fn property_notify_trampoline<P>(
+                this: *mut imp::#InstanceNameFfi,
+                _pspec: *mut gobject_ffi::GParamSpec,
+                f: glib_ffi::gpointer,
+            )
+                where
+                P: IsA<#InstanceName>,
+            {
+                #callback_guard
+
+                let f: &&(Fn(&P) + 'static) = mem::transmute(f);
+
+                f(&#InstanceName::from_glib_borrow(this).downcast_unchecked())
+            }
+        }
+    }
+}
+
+/// Generates the call to the `g_param_spec_*()` function that
+/// corresponds to the property's type.
+fn param_spec(prop: &Property) -> Tokens {
+    let name = canonical_param_name(prop.name.as_ref());
+    let name = CStringLiteral(&name);
+    let nick = optional_cstring(&prop.nick);
+    let blurb = optional_cstring(&prop.blurb);
+    let flags = param_flags(prop);
+
+    match prop.type_ {
+        Ty::Bool(_) => {
+            let default = match prop.default {
+                Some(ref default) => quote_cs! { <bool as ToGlib>::to_glib(&#default) },
+                None => quote_cs! { glib_ffi::GFALSE },
+            };
+
+            quote_cs! {
+                gobject_ffi::g_param_spec_boolean(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    #default,
+                    #flags,
+                )
+            }
+        }
+
+        Ty::Integer(ref i) => {
+            let spec_fn = Ident::from(match i.as_ref() {
+                "i8" => "g_param_spec_char",
+                "i16" | "i32" => "g_param_spec_int",
+                "i64" => "g_param_spec_int64",
+                "isize" => "g_param_spec_long",
+                "u8" => "g_param_spec_uchar",
+                "u16" | "u32" => "g_param_spec_uint",
+                "u64" => "g_param_spec_uint64",
+                "usize" => "g_param_spec_ulong",
+                _ => unreachable!(),
+            });
+            // The g_param_spec_*() functions for promoted types take
+            // wider arguments
+            let glib_type = promoted_integer_type(i).unwrap_or_else(|| quote_cs! { #i });
+            // The default bounds for isize/usize are those of the C
+            // long, so that they don't wrap around if it's narrower
+            let bounds_type = match i.as_ref() {
+                "isize" | "usize" => glib_type.clone(),
+                _ => quote_cs! { #i },
+            };
+            let minimum = prop.minimum
+                .as_ref()
+                .map(|m| quote_cs! { #m })
+                .unwrap_or_else(|| quote_cs! { <#bounds_type>::min_value() });
+            let maximum = prop.maximum
+                .as_ref()
+                .map(|m| quote_cs! { #m })
+                .unwrap_or_else(|| quote_cs! { <#bounds_type>::max_value() });
+            let default = prop.default
+                .as_ref()
+                .map(|d| quote_cs! { #d })
+                .unwrap_or_else(|| quote_cs! { 0 });
+
+            quote_cs! {
+                gobject_ffi::#spec_fn(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    (#minimum) as #glib_type,
+                    (#maximum) as #glib_type,
+                    (#default) as #glib_type,
+                    #flags,
+                )
+            }
+        }
+
+        Ty::Float(ref f) => {
+            let spec_fn = Ident::from(match f.as_ref() {
+                "f32" => "g_param_spec_float",
+                "f64" => "g_param_spec_double",
+                _ => unreachable!(),
+            });
+            let minimum = prop.minimum
+                .as_ref()
+                .map(|m| quote_cs! { #m })
+                .unwrap_or_else(|| quote_cs! { ::std::#f::MIN });
+            let maximum = prop.maximum
+                .as_ref()
+                .map(|m| quote_cs! { #m })
+                .unwrap_or_else(|| quote_cs! { ::std::#f::MAX });
+            let default = prop.default
+                .as_ref()
+                .map(|d| quote_cs! { #d })
+                .unwrap_or_else(|| quote_cs! { 0.0 });
+
+            quote_cs! {
+                gobject_ffi::#spec_fn(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    #minimum,
+                    #maximum,
+                    #default,
+                    #flags,
+                )
+            }
+        }
+
+        Ty::String(_) | Ty::Option(_) => {
+            // Use an empty string as the default for non-optional
+            // strings, so that their getters don't have to deal with NULL.
+            let default = match (&prop.default, &prop.type_) {
+                (&Some(ref default), _) => Some(string_literal_value(default)),
+                (&None, &Ty::String(_)) => Some(String::new()),
+                (&None, _) => None,
+            };
+            let default = optional_cstring(&default);
+
+            quote_cs! {
+                gobject_ffi::g_param_spec_string(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    #default,
+                    #flags,
+                )
+            }
+        }
+
+        Ty::Enum(ref e) => {
+            // hir fills in the first variant if there is no #[default]
+            let default = prop.default.as_ref().unwrap();
+
+            quote_cs! {
+                gobject_ffi::g_param_spec_enum(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    <#e as glib::StaticType>::static_type().to_glib(),
+                    <#e as ToGlib>::to_glib(&#default),
+                    #flags,
+                )
+            }
+        }
+
+        Ty::Flags(ref f) => {
+            // hir fills in `Foo::empty()` if there is no #[default]
+            let default = prop.default.as_ref().unwrap();
+
+            quote_cs! {
+                gobject_ffi::g_param_spec_flags(
+                    #name as *const u8 as *const i8,
+                    #nick,
+                    #blurb,
+                    <#f as glib::StaticType>::static_type().to_glib(),
+                    <#f as ToGlib>::to_glib(&(#default)),
+                    #flags,
+                )
+            }
+        }
+
+        Ty::Boxed(ref b) => quote_cs! {
+            gobject_ffi::g_param_spec_boxed(
+                #name as *const u8 as *const i8,
+                #nick,
+                #blurb,
+                <#b as glib::StaticType>::static_type().to_glib(),
+                #flags,
+            )
+        },
+
+        _ => unreachable!(), // hir rejects properties of other types
+    }
+}
+
+/// Gets the value of a string literal, as validated by hir for the
+/// `#[default]` of string properties.
+fn string_literal_value(expr: &syn::Expr) -> String {
+    match *expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(ref s),
+            ..
+        }) => s.value(),
+        _ => unreachable!(),
+    }
+}
+
+/// Whether a property gets a public setter in the InstanceExt trait.
+/// Construct-only properties can only be set when creating the object.
+fn is_settable(prop: &Property) -> bool {
+    prop.is_writable() && !prop.construct_only
+}
+
+/// Generates the `GParamFlags` for a property, like
+/// `G_PARAM_READABLE | G_PARAM_WRITABLE`.
+fn param_flags(prop: &Property) -> Tokens {
+    let mut flags = Vec::new();
+
+    if prop.is_readable() {
+        flags.push(quote_cs! { gobject_ffi::G_PARAM_READABLE });
+    }
+    if prop.is_writable() {
+        flags.push(quote_cs! { gobject_ffi::G_PARAM_WRITABLE });
+    }
+    if prop.construct {
+        flags.push(quote_cs! { gobject_ffi::G_PARAM_CONSTRUCT });
+    }
+    if prop.construct_only {
+        flags.push(quote_cs! { gobject_ffi::G_PARAM_CONSTRUCT_ONLY });
+    }
+    if prop.explicit_notify {
+        flags.push(quote_cs! { gobject_ffi::G_PARAM_EXPLICIT_NOTIFY });
+    }
+
+    quote_cs! { #(#flags)|* }
+}
+
+/// Generates a pointer to a C string, or a null pointer if there is
+/// no string.  This is for the optional nick and blurb of a `GParamSpec`.
+fn optional_cstring(s: &Option<String>) -> Tokens {
+    match *s {
+        Some(ref s) => {
+            let s = CStringLiteral(s);
+            quote_cs! { #s as *const u8 as *const i8 }
+        }
+        None => quote_cs! { ptr::null() },
+    }
+}
+
+/// Generates the equivalent of C's `G_OBJECT_WARN_INVALID_PROPERTY_ID()`.
+///
+/// This expects `obj`, `property_id` and `pspec` in scope, as in
+/// the arguments to `set_property()` and `get_property()`.
+fn warn_invalid_property_id() -> Tokens {
+    quote_cs! {
+        let obj_type = (*(*(obj as *mut gobject_ffi::GTypeInstance)).g_class).g_type;
+
+        glib_ffi::g_log(
+            b"GLib-GObject\0" as *const u8 as *const i8,
+            glib_ffi::G_LOG_LEVEL_WARNING,
+            b"invalid property id %u for \"%s\" of type '%s' in '%s'\0" as *const u8 as *const i8,
+            property_id,
+            (*pspec).name,
+            gobject_ffi::g_type_name((*pspec).value_type),
+            gobject_ffi::g_type_name(obj_type),
+        );
+    }
+}
+
+/// From a property called `foo`, generate `foo_getter_impl`.  This is
+/// the function with the body of the property's `get` block.
+fn property_getter_impl_name(prop: &Property) -> Ident {
+    Ident::from(format!(
+        "{}_getter_impl",
+        lower_case_instance_name(prop.name.as_ref())
+    ))
+}
+
+/// From a property called `foo`, generate `foo_setter_impl`.  This is
+/// the function with the body of the property's `set` block.
+fn property_setter_impl_name(prop: &Property) -> Ident {
+    Ident::from(format!(
+        "{}_setter_impl",
+        lower_case_instance_name(prop.name.as_ref())
+    ))
+}
+
+/// From a property called `foo`, generate `pspec_foo`.  This is the
+/// function that returns the property's `GParamSpec`.
+fn property_pspec_name(prop: &Property) -> Ident {
+    Ident::from(format!("pspec_{}", lower_case_instance_name(prop.name.as_ref())))
+}
+
+/// From a property called `foo`, generate `notify_foo`.  This is the
+/// helper that emits the property's "notify" signal.
+fn property_notify_name(prop: &Property) -> Ident {
+    Ident::from(format!("notify_{}", lower_case_instance_name(prop.name.as_ref())))
+}
+
+/// From a property called `foo`, generate `get_foo`.  This is used for
+/// the public getter in the InstanceExt trait.
+fn property_get_name(prop: &Property) -> Ident {
+    Ident::from(format!("get_{}", lower_case_instance_name(prop.name.as_ref())))
+}
+
+/// From a property called `foo`, generate `set_foo`.  This is used for
+/// the public setter in the InstanceExt trait.
+fn property_set_name(prop: &Property) -> Ident {
+    Ident::from(format!("set_{}", lower_case_instance_name(prop.name.as_ref())))
+}
+
+/// From a property called `foo`, generate `connect_foo_notify`.  This
+/// is used for the public method in the InstanceExt trait that
+/// connects to the property's "notify" signal.
+fn property_connect_notify_name(prop: &Property) -> Ident {
+    Ident::from(format!(
+        "connect_{}_notify",
+        lower_case_instance_name(prop.name.as_ref())
+    ))
+}
@@ -1,11 +1,12 @@
 use quote::Tokens;
-use syn::{Ident, Path};
+use syn::Ident;
 
 use glib_utils::*;
-use hir::{FnArg, Signal, Slot, Ty};
+use hir::{Accumulator, Signal, Slot, Ty};
 
 use super::class::ClassContext;
 use super::cstringident::CStringIdent;
+use super::values;
 
 impl<'ast> ClassContext<'ast> {
     pub fn signal_trampolines(&self) -> Vec<Tokens> {
@@ -20,6 +21,7 @@ impl<'ast> ClassContext<'ast> {
                 let input_types = signal.sig.input_arg_types();
                 let arg_names = sig.input_args_from_glib_types();
                 let output = &sig.output;
+                let glib_output = sig.output_glib_type();
 
                 let ret = quote_cs! {
                     f(&#InstanceName::from_glib_borrow(this).downcast_unchecked(), #arg_names)
@@ -31,7 +33,7 @@ impl<'ast> ClassContext<'ast> {
                         this: *mut imp::#InstanceNameFfi,
                         #c_inputs
                         f: glib_ffi::gpointer,
-                    ) -> #output
+                    ) -> #glib_output
                         where
                         P: IsA<#InstanceName>,
                     {
@@ -49,10 +51,6 @@ impl<'ast> ClassContext<'ast> {
     pub fn signal_declarations(&self) -> Vec<Tokens> {
         self.signals()
             .map(|signal| {
-                // FIXME: we are not passing signal flags
-                //
-                // FIXME: We are not passing a class_closure, marshaller, etc.
-
                 let get_type_fn_name = self.instance_get_type_fn_name();
                 let signal_id_name = signal_id_name(&signal);
                 let signal_name = CStringIdent(signal.sig.name);
@@ -60,32 +58,48 @@ impl<'ast> ClassContext<'ast> {
                 assert!(signal.sig.inputs.len() > 0);
                 let n_params = (signal.sig.inputs.len() - 1) as u32;
 
-                let param_gtypes: Vec<Path> = signal.sig.inputs.iter()
-                    .skip(1) // skip &self
-                    .map(|arg| {
-                        if let FnArg::Arg { ref ty, .. } = arg {
-                            ty.to_gtype_path()
-                        } else {
-                            unreachable!();
-                        }
-                    })
+                let param_gtypes: Vec<Tokens> = signal.sig.arg_types()
+                    .map(|ty| ty.to_gtype())
                     .collect();
 
-                let return_gtype = signal.sig.output.to_gtype_path();
+                let return_gtype = signal.sig.output.to_gtype();
+                let flags = signal_flags(signal);
+                let ClassName = self.ClassName;
+                let signalname = signal.sig.name;
+                let accumulator = match signal.accumulator {
+                    None => quote_cs! { None },
+                    Some(Accumulator::FirstWins) => {
+                        quote_cs! { Some(gobject_sys::g_signal_accumulator_first_wins) }
+                    }
+                    Some(Accumulator::TrueHandled) => {
+                        quote_cs! { Some(gobject_sys::g_signal_accumulator_true_handled) }
+                    }
+                    Some(Accumulator::Custom(_)) => {
+                        let accumulator_trampoline = signal_accumulator_trampoline_name(signal);
+                        quote_cs! { Some(#accumulator_trampoline) }
+                    }
+                };
 
                 quote_cs! {
                     let param_gtypes = [#(#param_gtypes),*];
 
-                    // FIXME: we are using G_SIGNAL_RUN_LAST all the time so that signals
-                    // with return values will work.  We should do this automatically only
-                    // for signals with return values, or let the user specify the flags and
-                    // check that they match gobject's requirements.
+                    // The class closure calls the function in the signal's
+                    // class slot, so that subclasses can override it.  With
+                    // no c_marshaller, GObject uses its generic marshaller,
+                    // which calls the slot with the C types of the arguments.
+                    let class_offset = {
+                        let klass = &*(klass as *const #ClassName);
+                        &klass.#signalname as *const _ as usize - klass as *const _ as usize
+                    };
+                    let class_closure =
+                        gobject_sys::g_signal_type_cclosure_new(#get_type_fn_name(), class_offset as u32);
+
                     PRIV.#signal_id_name =
                         gobject_sys::g_signal_newv (#signal_name as *const u8 as *const i8,
                                                     #get_type_fn_name(),
-                                                    gobject_sys::G_SIGNAL_RUN_LAST,    // flags
-                                                    ptr::null_mut(),                   // class_closure,
-                                                    None,                              // accumulator
+                                                    #flags,                            // flags
+                                                    class_closure,                     // class_closure,
+                                                    #accumulator,                      // accumulator
                                                     ptr::null_mut(),                   // accu_data
                                                     None,                              // c_marshaller,
                                                     #return_gtype,                     // return_type
@@ -97,54 +111,154 @@ impl<'ast> ClassContext<'ast> {
             .collect()
     }
 
+    /// Generates a C-ABI `GSignalAccumulator` for each signal with a
+    /// custom `#[accumulator(...)]`.  It converts the GValues to Rust
+    /// values, calls the user's function, and stores the accumulated
+    /// value back.
+    pub fn signal_accumulator_trampolines(&self) -> Vec<Tokens> {
+        self.signals()
+            .filter_map(|signal| match signal.accumulator {
+                Some(Accumulator::Custom(ref path)) => Some((signal, path)),
+                _ => None,
+            })
+            .map(|(signal, path)| {
+                let accumulator_trampoline = signal_accumulator_trampoline_name(signal);
+                let callback_guard = glib_callback_guard();
+                let output = &signal.sig.output;
+
+                let accumulated = values::from_value(output, &quote_cs! { *return_accu });
+                let value = values::from_value(output, &quote_cs! { *handler_return });
+                let new_value = values::to_value(output, &quote_cs! { accumulated });
+
+                quote_cs! {
+                    unsafe extern "C" fn #accumulator_trampoline(
+                        _ihint: *mut gobject_ffi::GSignalInvocationHint,
+                        return_accu: *mut gobject_ffi::GValue,
+                        handler_return: *const gobject_ffi::GValue,
+                        _data: glib_ffi::gpointer,
+                    ) -> glib_ffi::gboolean {
+                        #callback_guard
+
+                        let return_accu = return_accu as *mut glib::Value;
+                        let handler_return = handler_return as *const glib::Value;
+
+                        let mut accumulated: #output = #accumulated;
+                        let value: #output = #value;
+                        let continue_emission: bool = #path(&mut accumulated, value);
+
+                        // g_value_copy() frees the previous contents
+                        let new_value = #new_value;
+                        gobject_ffi::g_value_copy(new_value.to_glib_none().0, return_accu as *mut _);
+
+                        continue_emission.to_glib()
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub fn signal_emit_methods(&self) -> Vec<Tokens> {
         self.signals()
-            .map(|signal| {
+            .flat_map(|signal| {
                 let emit_name = emit_signalname(signal);
-                let signal_id_name = signal_id_name(&signal);
-                let rust_params = &signal.sig.inputs;
-                let rust_return_ty = &signal.sig.output;
-                let signal_params = signal.sig.input_args_to_glib_values();
-                let return_gtype = signal.sig.output.to_gtype_path();
-
-                let (initialize_return_value, convert_return_value_to_rust) = match rust_return_ty {
-                    Ty::Unit => (quote_cs!{}, quote_cs! { () }),
-
-                    _ => (
-                        quote_cs! {
-                            gobject_sys::g_value_init(ret.to_glib_none_mut().0, #return_gtype);
-                        },
-                        quote_cs! {
-                            if ret.type_() == glib::Type::Invalid {
-                                unreachable!();
-                            } else {
-                                ret.get().unwrap()
-                            }
-                        },
-                    ),
-                };
+                let mut methods = vec![self.signal_emit_method(signal, emit_name, None)];
 
-                quote_cs! {
-                    #[allow(unused)]
-                    fn #emit_name(#(#rust_params),*) -> #rust_return_ty {
-                        // foo/imp.rs: increment()
-                        let params: &[glib::Value] = &[
-                            #signal_params
-                        ];
+                // For detailed signals, `emit_foo_with_detail(detail, ...)`
+                // emits "foo::detail"
+                if signal.flags.detailed {
+                    let emit_name = Ident::from(format!("{}_with_detail", emit_name.as_ref()));
+                    let detail = Ident::from("detail");
+                    methods.push(self.signal_emit_method(signal, emit_name, Some(detail)));
+                }
 
-                        unsafe {
-                            let mut ret = glib::Value::uninitialized();
+                methods
+            })
+            .collect()
+    }
+
+    fn signal_emit_method(&self, signal: &Signal, emit_name: Ident, detail: Option<Ident>) -> Tokens {
+        let signal_id_name = signal_id_name(&signal);
+        let (self_param, rust_params) = signal.sig.inputs.split_first().unwrap();
+        let rust_return_ty = &signal.sig.output;
+        let signal_params = signal.sig.input_args_to_glib_values();
+        let return_gtype = signal.sig.output.to_gtype();
+
+        let initialize_return_value = quote_cs! {
+            gobject_sys::g_value_init(ret.to_glib_none_mut().0, #return_gtype);
+        };
+        let (emit_return_ty, initialize_return_value, convert_return_value_to_rust) =
+            match rust_return_ty {
+                Ty::Unit => (quote_cs! { () }, quote_cs!{}, quote_cs! { () }),
 
-                            #initialize_return_value
+                // Without any handlers, the return value is left NULL
+                Ty::Owned(_) | Ty::Boxed(_) => (
+                    quote_cs! { Option<#rust_return_ty> },
+                    initialize_return_value,
+                    quote_cs! { ret.get::<#rust_return_ty>() },
+                ),
 
-                            gobject_sys::g_signal_emitv(
-                                mut_override(params.as_ptr()) as *mut gobject_sys::GValue,
-                                PRIV.#signal_id_name,
-                                0, // detail
-                                ret.to_glib_none_mut().0,
-                            );
+                _ => (
+                    quote_cs! { #rust_return_ty },
+                    initialize_return_value,
+                    values::from_value(rust_return_ty, &quote_cs! { ret }),
+                ),
+            };
 
-                            #convert_return_value_to_rust
+        let (detail_param, detail_quark) = match detail {
+            Some(detail) => (
+                quote_cs! { #detail: &str, },
+                quote_cs! { glib_ffi::g_quark_from_string(#detail.to_glib_none().0) },
+            ),
+            None => (quote_cs! {}, quote_cs! { 0 }),
+        };
+
+        quote_cs! {
+            #[allow(unused)]
+            fn #emit_name(#self_param, #detail_param #(#rust_params),*) -> #emit_return_ty {
+                // foo/imp.rs: increment()
+                let params: &[glib::Value] = &[
+                    #signal_params
+                ];
+
+                unsafe {
+                    let mut ret = glib::Value::uninitialized();
+
+                    #initialize_return_value
+
+                    gobject_sys::g_signal_emitv(
+                        mut_override(params.as_ptr()) as *mut gobject_sys::GValue,
+                        PRIV.#signal_id_name,
+                        #detail_quark, // detail
+                        ret.to_glib_none_mut().0,
+                    );
+
+                    #convert_return_value_to_rust
+                }
+            }
+        }
+    }
+
+    /// Generates a `Foo::signal_id_foo()` function for each signal.  It
+    /// returns the signal id as stored in `PRIV` by `class_init()`, so
+    /// that the `FooExt` functions don't need to look up the signal by
+    /// name.
+    pub fn signal_id_fns(&self) -> Vec<Tokens> {
+        let get_type_fn_name = self.instance_get_type_fn_name();
+
+        self.signals()
+            .map(|signal| {
+                let signal_id_name = signal_id_name(signal);
+                let signal_id_fn_name = signal_id_fn_name(signal);
+
+                quote_cs! {
+                    pub fn #signal_id_fn_name() -> u32 {
+                        unsafe {
+                            // Make sure that class_init() has run, like in the
+                            // pspec_foo() functions.
+                            let klass = gobject_ffi::g_type_class_ref(#get_type_fn_name());
+                            gobject_ffi::g_type_class_unref(klass);
+
+                            PRIV.#signal_id_name
                         }
                     }
                 }
@@ -166,12 +280,50 @@ impl<'ast> ClassContext<'ast> {
     }
 }
 
+/// Generates the `GSignalFlags` for `g_signal_newv()`, like
+/// `G_SIGNAL_RUN_LAST | G_SIGNAL_ACTION`.
+fn signal_flags(signal: &Signal) -> Tokens {
+    let flags = &signal.flags;
+    let flags = [
+        (flags.run_first, "G_SIGNAL_RUN_FIRST"),
+        (flags.run_last, "G_SIGNAL_RUN_LAST"),
+        (flags.run_cleanup, "G_SIGNAL_RUN_CLEANUP"),
+        (flags.no_recurse, "G_SIGNAL_NO_RECURSE"),
+        (flags.detailed, "G_SIGNAL_DETAILED"),
+        (flags.action, "G_SIGNAL_ACTION"),
+        (flags.no_hooks, "G_SIGNAL_NO_HOOKS"),
+        (flags.deprecated, "G_SIGNAL_DEPRECATED"),
+    ].iter()
+        .filter(|&&(set, _)| set)
+        .map(|&(_, flag)| Ident::from(flag))
+        .collect::<Vec<_>>();
+
+    // There is always at least one of the run_* flags
+    quote_cs! { #(gobject_sys::#flags)|* }
+}
+
 /// From a signal called `foo`, generate `foo_signal_id`.  This is used to
 /// store the signal ids from g_signal_newv() in the Class structure.
 fn signal_id_name<'ast>(signal: &'ast Signal) -> Ident {
     Ident::from(format!("{}_signal_id", signal.sig.name.as_ref()))
 }
 
+/// From a signal called `foo` generate a `foo_signal_accumulator_trampoline`
+/// identifier.  This is used for the `GSignalAccumulator` of signals with
+/// a custom `#[accumulator(...)]`.
+fn signal_accumulator_trampoline_name(signal: &Signal) -> Ident {
+    Ident::from(format!(
+        "{}_signal_accumulator_trampoline",
+        signal.sig.name.as_ref()
+    ))
+}
+
+/// From a signal called `foo`, generate `signal_id_foo`.  This is used for
+/// the public function that returns the signal id.
+pub fn signal_id_fn_name(signal: &Signal) -> Ident {
+    Ident::from(format!("signal_id_{}", signal.sig.name.as_ref()))
+}
+
 /// From a signal called `foo` generate a `foo_trampoline` identifier.  This is used
 /// for the functions that get passed to g_signal_connect().
 pub fn signal_trampoline_name(signal: &Signal) -> Ident {
@@ -187,6 +339,36 @@ pub fn connect_signalname(signal: &Signal) -> Ident {
     Ident::from(format!("connect_{}", signal.sig.name.as_ref()))
 }
 
+/// From a signal called `foo` generate a `connect_foo_after` identifier.
+/// This is used for the public methods in the InstanceExt trait.
+pub fn connect_signalname_after(signal: &Signal) -> Ident {
+    Ident::from(format!("connect_{}_after", signal.sig.name.as_ref()))
+}
+
+/// From a signal called `foo` generate a `block_foo` identifier.  This is
+/// used for the public methods in the InstanceExt trait.
+pub fn block_signalname(signal: &Signal) -> Ident {
+    Ident::from(format!("block_{}", signal.sig.name.as_ref()))
+}
+
+/// From a signal called `foo` generate an `unblock_foo` identifier.  This
+/// is used for the public methods in the InstanceExt trait.
+pub fn unblock_signalname(signal: &Signal) -> Ident {
+    Ident::from(format!("unblock_{}", signal.sig.name.as_ref()))
+}
+
+/// From a signal called `foo` generate a `stop_emission_foo` identifier.
+/// This is used for the public methods in the InstanceExt trait.
+pub fn stop_emission_signalname(signal: &Signal) -> Ident {
+    Ident::from(format!("stop_emission_{}", signal.sig.name.as_ref()))
+}
+
+/// From a signal called `foo` generate a `connect_foo_detailed` identifier.
+/// This is used for detailed signals in the InstanceExt trait.
+pub fn connect_signalname_detailed(signal: &Signal) -> Ident {
+    Ident::from(format!("connect_{}_detailed", signal.sig.name.as_ref()))
+}
+
 /// From a signal called `foo` generate a `emit_foo` identifier.  This is used
 /// for the user's implementations of methods.
 fn emit_signalname(signal: &Signal) -> Ident {
@@ -1,10 +1,12 @@
-use proc_macro::{Diagnostic, Level};
-use proc_macro2::{Delimiter, Group, Span, TokenTree};
+use proc_macro2::Span;
 use quote::{ToTokens, Tokens};
-use syn::spanned::Spanned;
+use syn::Ident;
 
 use hir::{FnArg, FnSig, Ty};
 
+use super::g_critical;
+use super::values;
+
 impl<'ast> FnSig<'ast> {
     /// Generates the Glib type name of the function's return value
     ///
@@ -12,7 +14,7 @@ impl<'ast> FnSig<'ast> {
     /// bool`, then this function will generate something that
     /// resolves to `glib_sys::gboolean`.
     pub fn output_glib_type<'a>(&'a self) -> impl ToTokens + 'a {
-        ToGlibType(&self.output, self)
+        ToGlibType(&self.output, Transfer::Full)
     }
 
     /// Generates an argument list just with Rust types, suitable for `Fn` signatures, without
@@ -100,23 +102,51 @@ impl<'ast> FnSig<'ast> {
     /// by code which generates a function callable from C that wraps
     /// Rust code.
     pub fn ret_to_glib<'a, T: ToTokens + 'a>(&'a self, tokens: T) -> impl ToTokens + 'a {
-        ToGlib(&self.output, tokens)
+        ToGlib(&self.output, tokens, Transfer::Full)
     }
 
     pub fn ret_from_glib_fn<'a, V: ToTokens>(&'a self, v: &'a V) -> impl ToTokens + 'a {
         let mut tokens = Tokens::new();
         v.to_tokens(&mut tokens);
-        FromGlib(&self.output, tokens)
+        FromGlib(&self.output, tokens, Transfer::Full)
+    }
+}
+
+/// Returns the C type for integer types that don't have a GType of
+/// their own, or `None` for the types that do.
+///
+/// 16-bit integers get promoted to `gint`/`guint`, and `isize`/`usize`
+/// are passed as `glong`/`gulong`.  This way the C ABI uses the same
+/// types as GValues for signals and properties.
+pub fn promoted_integer_type(ident: &Ident) -> Option<Tokens> {
+    match ident.as_ref() {
+        "i16" => Some(quote_cs! { i32 }),
+        "u16" => Some(quote_cs! { u32 }),
+        "isize" => Some(quote_cs! { ::std::os::raw::c_long }),
+        "usize" => Some(quote_cs! { ::std::os::raw::c_ulong }),
+        _ => None,
     }
 }
 
-struct ToGlibType<'ast>(&'ast Ty<'ast>, &'ast FnSig<'ast>);
+/// How ownership of a value is passed between C and Rust.
+///
+/// Following the usual GObject conventions, arguments are passed with
+/// transfer none and return values with transfer full.  This only
+/// makes a difference for types that own memory, like strings and
+/// objects.
+#[derive(Copy, Clone)]
+enum Transfer {
+    None,
+    Full,
+}
+
+struct ToGlibType<'ast>(&'ast Ty<'ast>, Transfer);
 
 impl<'ast> ToTokens for ToGlibType<'ast> {
     fn to_tokens(&self, tokens: &mut Tokens) {
         match *self.0 {
             Ty::Unit => self.0.to_tokens(tokens),
-            Ty::Char(i) | Ty::Bool(i) => {
+            Ty::Char(i) | Ty::Bool(i) | Ty::Enum(i) | Ty::Flags(i) => {
                 (quote_cs! {
                     <#i as ToGlib>::GlibType
                 }).to_tokens(tokens);
@@ -126,31 +156,45 @@ impl<'ast> ToTokens for ToGlibType<'ast> {
                     <#t as GlibPtrDefault>::GlibType
                 }).to_tokens(tokens);
             }
-            Ty::Integer(i) => i.to_tokens(tokens),
-            Ty::Owned(_) => {
-                Diagnostic::spanned(
-                    self.0.span().unstable(),
-                    Level::Error,
-                    "unimplemented glib type for owned types",
-                ).emit();
-                (quote! {
-                    ()
+            Ty::Integer(i) => match promoted_integer_type(&i) {
+                Some(t) => t.to_tokens(tokens),
+                None => i.to_tokens(tokens),
+            },
+            Ty::Float(i) => i.to_tokens(tokens),
+            Ty::Str(_) | Ty::String(_) => match self.1 {
+                Transfer::None => (quote_cs! { *const ::std::os::raw::c_char }).to_tokens(tokens),
+                Transfer::Full => (quote_cs! { *mut ::std::os::raw::c_char }).to_tokens(tokens),
+            },
+            // None is passed as NULL, so optional values use the same pointer types
+            Ty::Option(ref t) => ToGlibType(t, self.1).to_tokens(tokens),
+            Ty::Boxed(_) => match self.1 {
+                Transfer::None => (quote_cs! { glib_ffi::gconstpointer }).to_tokens(tokens),
+                Transfer::Full => (quote_cs! { glib_ffi::gpointer }).to_tokens(tokens),
+            },
+            Ty::Owned(t) => {
+                (quote_cs! {
+                    <#t as GlibPtrDefault>::GlibType
                 }).to_tokens(tokens);
             }
         }
     }
 }
 
-struct ToGlib<'ast, T>(&'ast Ty<'ast>, T);
+struct ToGlib<'ast, T>(&'ast Ty<'ast>, T, Transfer);
 
 impl<'ast, T: ToTokens> ToTokens for ToGlib<'ast, T> {
     fn to_tokens(&self, tokens: &mut Tokens) {
         let expr = &self.1;
         match *self.0 {
             // no conversion necessary
-            Ty::Unit | Ty::Integer(_) => self.1.to_tokens(tokens),
+            Ty::Unit | Ty::Float(_) => self.1.to_tokens(tokens),
 
-            Ty::Char(i) | Ty::Bool(i) => {
+            Ty::Integer(i) => match promoted_integer_type(&i) {
+                Some(t) => (quote_cs! { (#expr) as #t }).to_tokens(tokens),
+                None => expr.to_tokens(tokens),
+            },
+
+            Ty::Char(i) | Ty::Bool(i) | Ty::Enum(i) | Ty::Flags(i) => {
                 (quote_cs! {
                     <#i as ToGlib>::to_glib(&#expr)
                 }).to_tokens(tokens);
@@ -160,56 +204,182 @@ impl<'ast, T: ToTokens> ToTokens for ToGlib<'ast, T> {
                     <#t as ToGlibPtr<_>>::to_glib_none(#expr).0
                 }).to_tokens(tokens);
             }
-            Ty::Owned(_) => {
-                Diagnostic::spanned(
-                    self.0.span().unstable(),
-                    Level::Error,
-                    "unimplemented glib type for owned types",
-                ).emit();
-                (quote! {
-                    ()
+            Ty::Str(i) => {
+                (quote_cs! {
+                    <#i as ToGlibPtr<_>>::to_glib_none(#expr).0
                 }).to_tokens(tokens);
             }
+            Ty::String(_) | Ty::Option(_) => {
+                let ty = self.0;
+                match self.2 {
+                    Transfer::None => (quote_cs! {
+                        <#ty as ToGlibPtr<_>>::to_glib_none(&#expr).0
+                    }).to_tokens(tokens),
+                    Transfer::Full => (quote_cs! {
+                        <#ty as ToGlibPtr<_>>::to_glib_full(&#expr)
+                    }).to_tokens(tokens),
+                }
+            }
+            Ty::Owned(t) => match self.2 {
+                Transfer::None => (quote_cs! {
+                    <#t as ToGlibPtr<_>>::to_glib_none(&#expr).0
+                }).to_tokens(tokens),
+                Transfer::Full => (quote_cs! {
+                    <#t as ToGlibPtr<_>>::to_glib_full(&#expr)
+                }).to_tokens(tokens),
+            },
+            // Boxed values are allocated with Box, like in the copy and
+            // free functions that we register for the type
+            Ty::Boxed(t) => match self.2 {
+                Transfer::None => (quote_cs! {
+                    &#expr as *const #t as glib_ffi::gconstpointer
+                }).to_tokens(tokens),
+                Transfer::Full => (quote_cs! {
+                    Box::into_raw(Box::new(#expr)) as glib_ffi::gpointer
+                }).to_tokens(tokens),
+            },
         }
     }
 }
 
-struct FromGlib<'ast>(&'ast Ty<'ast>, Tokens);
+struct FromGlib<'ast>(&'ast Ty<'ast>, Tokens, Transfer);
 
 impl<'ast> ToTokens for FromGlib<'ast> {
     fn to_tokens(&self, tokens: &mut Tokens) {
-        let needs_conversion = match *self.0 {
-            Ty::Unit => false, // no conversion necessary
-            Ty::Char(i) | Ty::Bool(i) => {
+        let expr = &self.1;
+        match *self.0 {
+            // no conversion necessary
+            Ty::Unit | Ty::Float(_) => expr.to_tokens(tokens),
+
+            Ty::Integer(i) => match promoted_integer_type(&i) {
+                Some(_) => (quote_cs! { (#expr) as #i }).to_tokens(tokens),
+                None => expr.to_tokens(tokens),
+            },
+
+            Ty::Char(i) | Ty::Bool(i) | Ty::Enum(i) | Ty::Flags(i) => {
                 (quote_cs! {
-                    <#i as FromGlib<_>>::from_glib
+                    <#i as FromGlib<_>>::from_glib(#expr)
                 }).to_tokens(tokens);
-                true
             }
             Ty::Borrowed(ref t) => {
                 (quote_cs! {
-                    &<#t as FromGlibPtrBorrow<_>>::from_glib_borrow
+                    &<#t as FromGlibPtrBorrow<_>>::from_glib_borrow(#expr)
+                }).to_tokens(tokens);
+            }
+            Ty::Str(_) => {
+                // C strings are not valid &str, so borrow from a copy
+                let null_check = null_check("string", self.2);
+                (quote_cs! {
+                    {
+                        let ptr = #expr;
+                        #null_check
+                        <String as FromGlibPtrNone<_>>::from_glib_none(ptr)
+                    }.as_str()
                 }).to_tokens(tokens);
-                true
             }
-            Ty::Integer(_) => false, // no conversion necessary
-            Ty::Owned(_) => {
-                Diagnostic::spanned(
-                    self.0.span().unstable(),
-                    Level::Error,
-                    "unimplemented glib type for owned types",
-                ).emit();
-                false
+            Ty::Option(ref t) if t.is_borrowed() => match **t {
+                Ty::Str(_) => (quote_cs! {
+                    <Option<String> as FromGlibPtrNone<_>>::from_glib_none(#expr)
+                        .as_ref()
+                        .map(String::as_str)
+                }).to_tokens(tokens),
+                Ty::Borrowed(ref t) => (quote_cs! {
+                    <Option<#t> as FromGlibPtrBorrow<_>>::from_glib_borrow(#expr).as_ref()
+                }).to_tokens(tokens),
+                _ => unreachable!(),
+            },
+            Ty::String(_) => {
+                let null_check = null_check("string", self.2);
+                match self.2 {
+                    Transfer::None => (quote_cs! {
+                        {
+                            let ptr = #expr;
+                            #null_check
+                            <String as FromGlibPtrNone<_>>::from_glib_none(ptr)
+                        }
+                    }).to_tokens(tokens),
+                    Transfer::Full => (quote_cs! {
+                        {
+                            let ptr = #expr;
+                            #null_check
+                            <String as FromGlibPtrFull<_>>::from_glib_full(ptr)
+                        }
+                    }).to_tokens(tokens),
+                }
+            }
+            Ty::Option(_) => {
+                let ty = self.0;
+                match self.2 {
+                    Transfer::None => (quote_cs! {
+                        <#ty as FromGlibPtrNone<_>>::from_glib_none(#expr)
+                    }).to_tokens(tokens),
+                    Transfer::Full => (quote_cs! {
+                        <#ty as FromGlibPtrFull<_>>::from_glib_full(#expr)
+                    }).to_tokens(tokens),
+                }
+            }
+            Ty::Owned(t) => {
+                let type_name = t.into_tokens().to_string().replace(" ", "");
+                let null_check = null_check(&format!("object type {}", type_name), self.2);
+                match self.2 {
+                    Transfer::None => (quote_cs! {
+                        {
+                            let ptr = #expr;
+                            #null_check
+                            <#t as FromGlibPtrNone<_>>::from_glib_none(ptr)
+                        }
+                    }).to_tokens(tokens),
+                    Transfer::Full => (quote_cs! {
+                        {
+                            let ptr = #expr;
+                            #null_check
+                            <#t as FromGlibPtrFull<_>>::from_glib_full(ptr)
+                        }
+                    }).to_tokens(tokens),
+                }
+            }
+            Ty::Boxed(t) => {
+                let null_check = null_check(&format!("boxed type {}", t.as_ref()), self.2);
+                match self.2 {
+                    Transfer::None => (quote_cs! {
+                        {
+                            let ptr = #expr as *const #t;
+                            #null_check
+                            Clone::clone(&*ptr)
+                        }
+                    }).to_tokens(tokens),
+                    Transfer::Full => (quote_cs! {
+                        {
+                            let ptr = #expr as *mut #t;
+                            #null_check
+                            *Box::from_raw(ptr)
+                        }
+                    }).to_tokens(tokens),
+                }
             }
-        };
-
-        if needs_conversion {
-            tokens.append(TokenTree::Group(Group::new(
-                Delimiter::Parenthesis,
-                self.1.clone().into_tokens().into(),
-            )));
-        } else {
-            self.1.to_tokens(tokens);
+        }
+    }
+}
+
+/// Generates a check for a NULL `ptr` that was passed from C where a
+/// value of the type described by `what` is required.
+///
+/// Arguments come from C callers, which may pass NULL; like
+/// `g_return_val_if_fail()`, this logs a critical and returns a zero
+/// value from the enclosing C function.  Return values are converted
+/// in Rust functions, which can't make up a value of the Rust type,
+/// so they panic after logging instead of dereferencing NULL.
+fn null_check(what: &str, transfer: Transfer) -> Tokens {
+    let critical = g_critical(&format!("unexpected NULL for {}", what), quote_cs!{});
+    let bail = match transfer {
+        Transfer::None => quote_cs! { return ::std::mem::zeroed(); },
+        Transfer::Full => quote_cs! { panic!("unexpected NULL for {}", #what); },
+    };
+
+    quote_cs! {
+        if ptr.is_null() {
+            #critical;
+            #bail
         }
     }
 }
@@ -243,7 +413,7 @@ impl<'ast> ToTokens for FnArgsWithGlibTypes<'ast> {
                 } => {
                     name.to_tokens(tokens);
                     Token!(:)([Span::call_site()]).to_tokens(tokens);
-                    ToGlibType(ty, self.0).to_tokens(tokens);
+                    ToGlibType(ty, Transfer::None).to_tokens(tokens);
                     Token!(,)([Span::call_site()]).to_tokens(tokens);
                 }
                 FnArg::SelfRef(..) => unreachable!(),
@@ -265,7 +435,7 @@ impl<'ast> ToTokens for ArgNamesFromGlib<'ast> {
                 } => {
                     let mut name_tokens = Tokens::new();
                     name.to_tokens(&mut name_tokens);
-                    FromGlib(ty, name_tokens).to_tokens(tokens);
+                    FromGlib(ty, name_tokens, Transfer::None).to_tokens(tokens);
                     Token!(,)([Span::call_site()]).to_tokens(tokens);
                 }
                 FnArg::SelfRef(..) => unreachable!(),
@@ -285,7 +455,7 @@ impl<'ast> ToTokens for ArgNamesToGlib<'ast> {
                     name,
                     mutbl: _,
                 } => {
-                    ToGlib(ty, name).to_tokens(tokens);
+                    ToGlib(ty, name, Transfer::None).to_tokens(tokens);
                     Token!(,)([Span::call_site()]).to_tokens(tokens);
                 }
                 FnArg::SelfRef(..) => unreachable!(),
@@ -308,9 +478,10 @@ impl<'ast> ToTokens for ArgNamesToGlibValues<'ast> {
                     code.to_tokens(tokens);
                 }
 
-                FnArg::Arg { name, .. } => {
+                FnArg::Arg { name, ref ty, .. } => {
+                    let value = values::to_value(ty, &name);
                     let code = quote_cs! {
-                        (&#name as &glib::ToValue).to_value(),
+                        #value,
                     };
 
                     code.to_tokens(tokens);
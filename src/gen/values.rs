@@ -0,0 +1,95 @@
+// Conversions between Rust values and `glib::Value`, for signal
+// emission and properties.
+//
+// Most types can just use the `ToValue` and `FromValue` traits from
+// glib-rs.  The exceptions are the integer types that don't have a
+// GType of their own; see `signatures::promoted_integer_type()`.
+
+use quote::{ToTokens, Tokens};
+use syn::Ident;
+
+use hir::Ty;
+
+use super::signatures::promoted_integer_type;
+
+/// Generates an expression that converts `expr`, of type `ty`, into a
+/// `glib::Value`.
+pub fn to_value<T: ToTokens>(ty: &Ty, expr: &T) -> Tokens {
+    if let Ty::Integer(ref i) = *ty {
+        match i.as_ref() {
+            "i16" | "u16" => {
+                let t = promoted_integer_type(i).unwrap();
+                return quote_cs! { glib::ToValue::to_value(&((#expr) as #t)) };
+            }
+
+            // glib-rs has no Value conversions for C longs, so we set
+            // them by hand.
+            "isize" | "usize" => {
+                let t = promoted_integer_type(i).unwrap();
+                let (gtype, setter, _) = long_value_functions(i.as_ref());
+
+                return quote_cs! {
+                    {
+                        let v = (#expr) as #t;
+                        let mut value = glib::Value::from_type(glib::Type::#gtype);
+                        #[allow(unused_unsafe)]
+                        let () = unsafe { gobject_ffi::#setter(value.to_glib_none_mut().0, v) };
+                        value
+                    }
+                };
+            }
+
+            _ => (),
+        }
+    }
+
+    quote_cs! { glib::ToValue::to_value(&#expr) }
+}
+
+/// Generates an expression that converts `value`, a `glib::Value`
+/// that holds a value of the GType for `ty`, into a Rust value of
+/// type `ty`.
+pub fn from_value<T: ToTokens>(ty: &Ty, value: &T) -> Tokens {
+    match *ty {
+        Ty::Integer(ref i) => match i.as_ref() {
+            "i16" | "u16" => {
+                let t = promoted_integer_type(i).unwrap();
+                quote_cs! { (#value).get::<#t>().unwrap() as #i }
+            }
+
+            "isize" | "usize" => {
+                let (_, _, getter) = long_value_functions(i.as_ref());
+
+                quote_cs! {
+                    {
+                        #[allow(unused_unsafe)]
+                        let v = unsafe { gobject_ffi::#getter((#value).to_glib_none().0) };
+                        v as #i
+                    }
+                }
+            }
+
+            _ => quote_cs! { (#value).get::<#i>().unwrap() },
+        },
+
+        // A NULL string is None, so don't unwrap() it
+        Ty::Option(ref inner) => quote_cs! { (#value).get::<#inner>() },
+
+        // GObject allows setting any string to NULL
+        Ty::String(_) => quote_cs! { (#value).get::<String>().unwrap_or_default() },
+
+        _ => quote_cs! { (#value).get::<#ty>().unwrap() },
+    }
+}
+
+/// Returns the `glib::Type` variant, and the names of the GValue setter
+/// and getter functions, for `isize` or `usize`.
+fn long_value_functions(ident: &str) -> (Ident, Ident, Ident) {
+    let (gtype, setter, getter) = match ident {
+        "isize" => ("ILong", "g_value_set_long", "g_value_get_long"),
+        "usize" => ("ULong", "g_value_set_ulong", "g_value_get_ulong"),
+        _ => unreachable!(),
+    };
+
+    (Ident::from(gtype), Ident::from(setter), Ident::from(getter))
+}
@@ -1,7 +1,7 @@
 #![allow(non_snake_case)]
 
-use ast;
 use quote::{ToTokens, Tokens};
+use syn::Path;
 
 pub fn tokens_GObject() -> Tokens {
     quote_cs! { glib::Object }
@@ -15,10 +15,8 @@ pub fn tokens_GObjectClassFfi() -> Tokens {
     quote_cs! { gobject_ffi::GObjectClass }
 }
 
-pub fn tokens_ParentInstance(class: &ast::Class) -> Tokens {
-    class
-        .extends
-        .as_ref()
+pub fn tokens_ParentInstance(extends: Option<&Path>) -> Tokens {
+    extends
         .map(|path| {
             let mut tokens = Tokens::new();
             path.to_tokens(&mut tokens);
@@ -27,15 +25,15 @@ pub fn tokens_ParentInstance(class: &ast::Class) -> Tokens {
         .unwrap_or_else(|| tokens_GObject())
 }
 
-pub fn tokens_ParentInstanceFfi(class: &ast::Class) -> Tokens {
-    let ParentInstance = tokens_ParentInstance(class);
+pub fn tokens_ParentInstanceFfi(extends: Option<&Path>) -> Tokens {
+    let ParentInstance = tokens_ParentInstance(extends);
     quote_cs! {
         <#ParentInstance as glib::wrapper::Wrapper>::GlibType
     }
 }
 
-pub fn tokens_ParentClassFfi(class: &ast::Class) -> Tokens {
-    let ParentInstance = tokens_ParentInstance(class);
+pub fn tokens_ParentClassFfi(extends: Option<&Path>) -> Tokens {
+    let ParentInstance = tokens_ParentInstance(extends);
     quote_cs! {
         <#ParentInstance as glib::wrapper::Wrapper>::GlibClassType
     }
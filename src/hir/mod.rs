@@ -6,7 +6,10 @@
 // We construct this view of the world from the raw Abstract Syntax
 // Tree (AST) from the previous stage.
 
+use std::cmp;
 use std::collections::HashMap;
+use std::mem;
+use std::os::raw::{c_long, c_ulong};
 
 use proc_macro::TokenStream;
 use proc_macro2::{Delimiter, Group, Span, TokenTree};
@@ -14,16 +17,25 @@ use quote::{ToTokens, Tokens};
 use syn::buffer::TokenBuffer;
 use syn::punctuated::Punctuated;
 use syn::synom::Synom;
-use syn::{self, parse_str, Block, Field, Ident, Path, ReturnType, Type};
+use syn::{self, parse_str, Block, Field, FieldsNamed, Ident, Path, ReturnType};
 
 use super::ast;
 use super::checking::*;
 use super::errors::*;
 use super::glib_utils::*;
+use super::param::*;
 
 pub struct Program<'ast> {
     pub classes: Classes<'ast>,
     pub interfaces: Interfaces<'ast>,
+    pub types: DeclaredTypes<'ast>,
+}
+
+/// The types declared with `enum`, `flags` and `boxed` items, which
+/// classes can use in their signatures and properties.
+pub struct DeclaredTypes<'ast> {
+    pub enums: Enums,
+    pub boxed: BoxedTypes<'ast>,
 }
 
 pub struct Classes<'ast> {
@@ -34,6 +46,14 @@ pub struct Interfaces<'ast> {
     items: HashMap<Ident, Interface<'ast>>,
 }
 
+pub struct Enums {
+    items: HashMap<Ident, Enum>,
+}
+
+pub struct BoxedTypes<'ast> {
+    items: HashMap<Ident, Boxed<'ast>>,
+}
+
 #[cfg_attr(rustfmt, rustfmt_skip)]
 pub struct Class<'ast> {
     pub name: Ident, // Foo
@@ -41,7 +61,8 @@ pub struct Class<'ast> {
     pub parent: Tokens,           // Parent
     pub parent_ffi: Tokens,       // ffi::Parent
     pub parent_class_ffi: Tokens, // ffi::ParentClass
-    pub implements: Vec<Path>,    // names of GTypeInterfaces
+    pub extends: Option<&'ast Path>, // None for GObject subclasses
+    pub implements: Vec<Ident>,   // names of GTypeInterfaces, in registration order
 
     // pub class_private: Option<&'ast ast::PrivateStruct>
 
@@ -52,17 +73,45 @@ pub struct Class<'ast> {
     // pub n_reserved_slots: usize,
 
     pub properties: Vec<Property<'ast>>,
-    pub overrides: HashMap<Ident, Vec<Method<'ast>>>,
+    pub overrides: HashMap<Ident, Vec<Override<'ast>>>,
+
+    // The methods from `impl interface Iface for Foo`, keyed by Iface
+    pub interface_impls: HashMap<Ident, Vec<Override<'ast>>>,
 }
 
 pub struct Interface<'ast> {
     pub name: Ident, // Foo
 
+    // From `interface Foo: Prerequisite + ...`; empty if it only requires GObject
+    pub prerequisites: Vec<&'ast Path>,
+
     // The order of these is important; it's the order of the slots in FooIface
     pub slots: Vec<Slot<'ast>>,
     // pub n_reserved_slots: usize,
 }
 
+pub struct Enum {
+    pub name: Ident,    // Foo
+    pub is_flags: bool, // GFlags instead of GEnum
+
+    // In declaration order; the values are the Rust discriminants for
+    // enums, or the bits of each flag
+    pub variants: Vec<EnumVariant>,
+}
+
+pub struct EnumVariant {
+    pub name: Ident,
+    pub value: i64, // i32 for enums, u32 for flags
+}
+
+pub struct Boxed<'ast> {
+    pub name: Ident, // Foo
+
+    // For `boxed struct Foo { ... }`; None for `boxed Foo;`, which
+    // registers a type defined elsewhere
+    pub definition: Option<(&'ast [syn::Attribute], &'ast FieldsNamed)>,
+}
+
 pub enum Slot<'ast> {
     Method(Method<'ast>),
     VirtualMethod(VirtualMethod<'ast>),
@@ -71,9 +120,28 @@ pub enum Slot<'ast> {
 
 pub struct Property<'ast> {
     pub name: Ident,
-    pub type_: &'ast Type,
-    pub getter: &'ast Block,
-    pub setter: PropertySetterBlock<'ast>,
+    pub type_: Ty<'ast>,
+    pub getter: Option<&'ast Block>, // None for write-only properties
+    pub setter: Option<PropertySetterBlock<'ast>>, // None for read-only properties
+    pub nick: Option<String>,
+    pub blurb: Option<String>,
+    pub default: Option<syn::Expr>, // validated against type_ and the range below
+    pub minimum: Option<syn::Expr>,
+    pub maximum: Option<syn::Expr>,
+    pub construct: bool,
+    pub construct_only: bool,
+    pub constructor_arg: bool, // passed to Foo::new(), in declaration order
+    pub explicit_notify: bool,
+}
+
+impl<'ast> Property<'ast> {
+    pub fn is_readable(&self) -> bool {
+        self.getter.is_some()
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.setter.is_some()
+    }
 }
 
 pub struct PropertySetterBlock<'ast> {
@@ -87,6 +155,16 @@ pub struct Method<'ast> {
     pub body: &'ast Block,
 }
 
+/// A virtual method or signal handler from `impl Parent for Foo`, which
+/// replaces the parent's implementation in its class slot, or a method
+/// from `impl interface Iface for Foo`, which fills a slot in the
+/// class's vtable for the interface.
+pub struct Override<'ast> {
+    pub sig: FnSig<'ast>,
+    pub body: &'ast Block,
+    pub is_signal: bool,
+}
+
 pub struct VirtualMethod<'ast> {
     pub sig: FnSig<'ast>,
     pub body: Option<&'ast Block>,
@@ -109,6 +187,16 @@ pub struct FnSig<'ast> {
     pub output: Ty<'ast>,
 }
 
+impl<'ast> FnSig<'ast> {
+    /// Returns the types of the arguments, without the `&self`.
+    pub fn arg_types<'a>(&'a self) -> impl Iterator<Item = &'a Ty<'ast>> {
+        self.inputs.iter().filter_map(|arg| match *arg {
+            FnArg::Arg { ref ty, .. } => Some(ty),
+            FnArg::SelfRef(..) => None,
+        })
+    }
+}
+
 pub enum FnArg<'ast> {
     SelfRef(Token!(&), Token!(self)),
     Arg {
@@ -119,17 +207,54 @@ pub enum FnArg<'ast> {
 }
 
 pub struct Signal<'ast> {
-    // FIXME: signal flags
     pub sig: FnSig<'ast>,
     pub body: Option<&'ast Block>,
+    pub flags: SignalFlags,
+    pub accumulator: Option<Accumulator>,
+}
+
+/// Combines the return values of a signal's handlers, from
+/// `#[accumulator(...)]`.
+pub enum Accumulator {
+    /// `g_signal_accumulator_first_wins()`: the first handler's value
+    /// is returned and the emission stops.
+    FirstWins,
+    /// `g_signal_accumulator_true_handled()`: the emission stops at the
+    /// first handler that returns `true`.
+    TrueHandled,
+    /// A function like `fn(accumulated: &mut T, value: T) -> bool` that
+    /// returns whether the emission should continue.
+    Custom(syn::Path),
+}
+
+/// The `GSignalFlags` for a signal, from attributes like `#[run_first]`.
+/// Signals without any of the `run_*` attributes are `#[run_last]`.
+#[derive(Default)]
+pub struct SignalFlags {
+    pub run_first: bool,
+    pub run_last: bool,
+    pub run_cleanup: bool,
+    pub no_recurse: bool,
+    pub detailed: bool,
+    pub action: bool,
+    pub no_hooks: bool,
+    pub deprecated: bool,
 }
 
+#[derive(Clone)]
 pub enum Ty<'ast> {
     Unit,
     Char(Ident),
     Bool(Ident),
     Borrowed(Box<Ty<'ast>>),
     Integer(Ident),
+    Float(Ident),
+    Str(Ident),            // &str
+    String(Ident),         // String
+    Option(Box<Ty<'ast>>), // nullable strings and objects
+    Enum(Ident),           // registered with `enum Foo { ... }`
+    Flags(Ident),          // registered with `flags Foo { ... }`
+    Boxed(Ident),          // registered with `boxed struct Foo { ... }` or `boxed Foo;`
     Owned(&'ast syn::Path),
 }
 
@@ -139,25 +264,87 @@ impl<'ast> Ty<'ast> {
             Ty::Unit => "gobject_sys::G_TYPE_NONE",
             Ty::Char(_) => "gobject_sys::G_TYPE_UINT", // <char as ToGlib>::GlibType = u32
             Ty::Bool(_) => "gobject_sys::G_TYPE_BOOLEAN",
-            Ty::Borrowed(_) => unimplemented!(),
+            Ty::Borrowed(_) => unreachable!("use to_gtype() for registered types"),
 
+            // 16-bit integers get promoted to 32 bits, and pointer-sized
+            // integers are passed as C longs; see gen::values.
             Ty::Integer(ref ident) => match ident.as_ref() {
                 "i8" => "gobject_sys::G_TYPE_CHAR",
-                "i16" => unimplemented!("should we promote i16 to i32?"),
+                "i16" => "gobject_sys::G_TYPE_INT",
                 "i32" => "gobject_sys::G_TYPE_INT",
                 "i64" => "gobject_sys::G_TYPE_INT64",
-                "isize" => unimplemented!(),
+                "isize" => "gobject_sys::G_TYPE_LONG",
 
                 "u8" => "gobject_sys::G_TYPE_UCHAR",
-                "u16" => unimplemented!("should we promote u16 to u32?"),
+                "u16" => "gobject_sys::G_TYPE_UINT",
                 "u32" => "gobject_sys::G_TYPE_UINT",
                 "u64" => "gobject_sys::G_TYPE_UINT64",
-                "usize" => unimplemented!(),
+                "usize" => "gobject_sys::G_TYPE_ULONG",
+
+                _ => unreachable!(),
+            },
 
+            Ty::Float(ref ident) => match ident.as_ref() {
+                "f32" => "gobject_sys::G_TYPE_FLOAT",
+                "f64" => "gobject_sys::G_TYPE_DOUBLE",
                 _ => unreachable!(),
             },
 
-            Ty::Owned(_) => unimplemented!(),
+            Ty::Str(_) | Ty::String(_) => "gobject_sys::G_TYPE_STRING",
+            Ty::Option(ref t) => t.to_gtype_string(),
+
+            Ty::Owned(_) | Ty::Enum(_) | Ty::Flags(_) | Ty::Boxed(_) => {
+                unreachable!("use to_gtype() for registered types")
+            }
+        }
+    }
+
+    /// Whether `to_gtype()` knows about this type.  Signals can only
+    /// use types that have a GType.
+    pub fn has_gtype(&self) -> bool {
+        match *self {
+            Ty::Borrowed(ref t) => match **t {
+                Ty::Owned(_) => true,
+                _ => false,
+            },
+            Ty::Option(ref t) => t.has_gtype(),
+            _ => true,
+        }
+    }
+
+    /// Generates an expression for the GType of this type.  Object types
+    /// and declared types get it from their implementation of
+    /// `glib::StaticType`.
+    pub fn to_gtype(&self) -> Tokens {
+        match *self {
+            Ty::Owned(path) => quote_cs! {
+                <#path as glib::StaticType>::static_type().to_glib()
+            },
+            Ty::Enum(ident) | Ty::Flags(ident) | Ty::Boxed(ident) => quote_cs! {
+                <#ident as glib::StaticType>::static_type().to_glib()
+            },
+            Ty::Borrowed(ref t) | Ty::Option(ref t) => t.to_gtype(),
+            _ => {
+                let path = self.to_gtype_path();
+                quote_cs! { #path }
+            }
+        }
+    }
+
+    /// Whether this is `&str` or a `&Foo` reference, which can only be
+    /// used for arguments.
+    pub fn is_borrowed(&self) -> bool {
+        match *self {
+            Ty::Borrowed(_) | Ty::Str(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this is `String`.
+    pub fn is_string(&self) -> bool {
+        match *self {
+            Ty::String(_) => true,
+            _ => false,
         }
     }
 
@@ -174,26 +361,212 @@ impl<'ast> Program<'ast> {
     pub fn from_ast_program(ast: &'ast ast::Program) -> Result<Program<'ast>> {
         check_program(ast)?;
 
-        let mut classes = Classes::new();
-        for class in ast.classes() {
-            classes.add(class)?;
+        // These go first, since classes can use them as types
+        let mut types = DeclaredTypes {
+            enums: Enums::new(),
+            boxed: BoxedTypes::new(),
+        };
+        for enum_ in ast.enums() {
+            types.enums.add(enum_)?;
         }
-        for impl_ in ast.impls() {
-            classes.add_impl(impl_)?;
+        for boxed in ast.boxed() {
+            if types.enums.get(&boxed.name).is_some() {
+                bail!("redefinition of type `{}`", boxed.name);
+            }
+            types.boxed.add(boxed)?;
         }
 
+        // Before classes, since they can implement interfaces
         let mut interfaces = Interfaces::new();
         for iface in ast.interfaces() {
-            interfaces.add(iface)?;
+            interfaces.add(iface, &types)?;
+        }
+
+        interfaces.check_prerequisites()?;
+
+        let mut classes = Classes::new();
+        for class in ast.classes() {
+            classes.add(class, &interfaces)?;
+        }
+        for impl_ in ast.impls() {
+            classes.add_impl(impl_, &types, &interfaces)?;
         }
+        classes.check_interfaces(&interfaces)?;
 
         Ok(Program {
             classes,
             interfaces,
+            types,
         })
     }
 }
 
+impl<'ast> DeclaredTypes<'ast> {
+    /// Returns the `Ty` for a type named `ident`, if it was declared in
+    /// the program.
+    fn lookup<'a>(&self, ident: Ident) -> Option<Ty<'a>> {
+        if let Some(enum_) = self.enums.get(&ident) {
+            if enum_.is_flags {
+                Some(Ty::Flags(ident))
+            } else {
+                Some(Ty::Enum(ident))
+            }
+        } else if self.boxed.get(&ident).is_some() {
+            Some(Ty::Boxed(ident))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'ast> BoxedTypes<'ast> {
+    fn new() -> BoxedTypes<'ast> {
+        BoxedTypes {
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &Ident) -> Option<&Boxed<'ast>> {
+        self.items.get(name)
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Boxed<'ast>> + 'a {
+        self.items.values()
+    }
+
+    fn add(&mut self, ast_boxed: &'ast ast::Boxed) -> Result<()> {
+        let name = ast_boxed.name;
+        let definition = match ast_boxed.fields {
+            Some(ref fields) => Some((&ast_boxed.attrs[..], fields)),
+            None => {
+                if !ast_boxed.attrs.is_empty() {
+                    bail!("`boxed {};` can't have attributes", name);
+                }
+                None
+            }
+        };
+
+        let prev = self.items.insert(name, Boxed { name, definition });
+        if prev.is_some() {
+            bail!("redefinition of type `{}`", name);
+        }
+        Ok(())
+    }
+}
+
+impl Enums {
+    fn new() -> Enums {
+        Enums {
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &Ident) -> Option<&Enum> {
+        self.items.get(name)
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Enum> + 'a {
+        self.items.values()
+    }
+
+    fn add(&mut self, ast_enum: &ast::Enum) -> Result<()> {
+        let name = ast_enum.name;
+        let is_flags = ast_enum.is_flags;
+        let kind = if is_flags { "flags" } else { "enum" };
+        if ast_enum.variants.is_empty() {
+            bail!("{} `{}` must have at least one variant", kind, name);
+        }
+
+        let mut variants: Vec<EnumVariant> = Vec::new();
+        let mut next_value = 0i128;
+        for variant in ast_enum.variants.iter() {
+            if !variant.attrs.is_empty() {
+                bail!(
+                    "variant `{}::{}` can't have attributes",
+                    name,
+                    variant.ident
+                );
+            }
+            match variant.fields {
+                syn::Fields::Unit => (),
+                _ => bail!("variant `{}::{}` can't have fields", name, variant.ident),
+            }
+
+            let value = match (is_flags, &variant.discriminant) {
+                (false, &Some((_, ref expr))) => match integer_literal_value(expr) {
+                    Some(v) => v,
+                    None => bail!(
+                        "value of `{}::{}` must be an integer literal",
+                        name,
+                        variant.ident
+                    ),
+                },
+                (false, &None) => next_value,
+                (true, &Some((_, ref expr))) => match flags_literal_value(expr) {
+                    Some(v) => v,
+                    None => bail!(
+                        "value of `{}::{}` must be an integer literal like `1 << 2`",
+                        name,
+                        variant.ident
+                    ),
+                },
+                (true, &None) => bail!(
+                    "flag `{}::{}` needs a value like `1 << 2`",
+                    name,
+                    variant.ident
+                ),
+            };
+            let (min, max, ty) = if is_flags {
+                (0, u32::max_value() as i128, "u32")
+            } else {
+                (i32::min_value() as i128, i32::max_value() as i128, "i32")
+            };
+            if value < min || value > max {
+                bail!(
+                    "value of `{}::{}` is out of range for `{}`",
+                    name,
+                    variant.ident,
+                    ty
+                );
+            }
+            let value = value as i64;
+
+            if variants.iter().any(|v| v.name == variant.ident) {
+                bail!("duplicate variant `{}::{}`", name, variant.ident);
+            }
+            // Rust enums can't have duplicate discriminants, but flags
+            // can have aliases
+            if !is_flags && variants.iter().any(|v| v.value == value) {
+                bail!(
+                    "duplicate value {} for `{}::{}`",
+                    value,
+                    name,
+                    variant.ident
+                );
+            }
+
+            variants.push(EnumVariant {
+                name: variant.ident,
+                value,
+            });
+            next_value = value as i128 + 1;
+        }
+
+        let prev = self.items.insert(
+            name,
+            Enum {
+                name,
+                is_flags,
+                variants,
+            },
+        );
+        if prev.is_some() {
+            bail!("redefinition of {} `{}`", kind, name);
+        }
+        Ok(())
+    }
+}
+
 impl<'ast> Classes<'ast> {
     fn new() -> Classes<'ast> {
         Classes {
@@ -209,20 +582,52 @@ impl<'ast> Classes<'ast> {
         self.items.iter().find(|c| c.1.name == name).unwrap().1
     }
 
-    fn add(&mut self, ast_class: &'ast ast::Class) -> Result<()> {
+    fn add(&mut self, ast_class: &'ast ast::Class, interfaces: &Interfaces) -> Result<()> {
+        // `class Foo: Iface` derives from GObject, since Iface is not a class
+        let mut parents = ast_class
+            .extends
+            .iter()
+            .chain(ast_class.implements.iter())
+            .peekable();
+        let extends = match parents.peek() {
+            Some(path) if interfaces.lookup_path(path).is_none() => parents.next(),
+            _ => None,
+        };
+
+        let mut implements = Vec::new();
+        for path in parents {
+            let iface = match interfaces.lookup_path(path) {
+                Some(iface) => iface.name,
+                None => bail!(
+                    "`{}` is not an interface defined in gobject_gen!",
+                    path.into_tokens()
+                ),
+            };
+            if implements.contains(&iface) {
+                bail!(
+                    "interface `{}` is listed more than once for class `{}`",
+                    iface,
+                    ast_class.name
+                );
+            }
+            implements.push(iface);
+        }
+
         let prev = self.items.insert(
             ast_class.name,
             Class {
                 name: ast_class.name,
-                gobject_parent: ast_class.extends.is_none(),
-                parent: tokens_ParentInstance(ast_class),
-                parent_ffi: tokens_ParentInstanceFfi(ast_class),
-                parent_class_ffi: tokens_ParentClassFfi(ast_class),
-                implements: Vec::new(),
+                gobject_parent: extends.is_none(),
+                parent: tokens_ParentInstance(extends),
+                parent_ffi: tokens_ParentInstanceFfi(extends),
+                parent_class_ffi: tokens_ParentClassFfi(extends),
+                extends,
+                implements,
                 private_fields: ast_class.fields.named.iter().collect(),
                 slots: Vec::new(),
                 properties: Vec::new(),
                 overrides: HashMap::new(),
+                interface_impls: HashMap::new(),
             },
         );
         if prev.is_some() {
@@ -231,7 +636,12 @@ impl<'ast> Classes<'ast> {
         Ok(())
     }
 
-    fn add_impl(&mut self, impl_: &'ast ast::Impl) -> Result<()> {
+    fn add_impl(
+        &mut self,
+        impl_: &'ast ast::Impl,
+        types: &DeclaredTypes,
+        interfaces: &Interfaces,
+    ) -> Result<()> {
         let class = match self.items.get_mut(&impl_.self_path) {
             Some(class) => class,
             None => bail!("impl for class that doesn't exist: {}", impl_.self_path),
@@ -243,6 +653,7 @@ impl<'ast> Classes<'ast> {
                 ..
             } => {
                 for item in impl_.items.iter() {
+                    let attrs = &item.attrs;
                     let item = match item.node {
                         ast::ImplItemKind::Method(ref m) => m,
                         ast::ImplItemKind::ReserveSlots(_) => {
@@ -252,27 +663,46 @@ impl<'ast> Classes<'ast> {
                             bail!("can't define props in a parent class impl");
                         }
                     };
-                    if item.signal.is_some() {
-                        bail!("can't implement signals for parent classes")
-                    }
-                    if !item.virtual_.is_some() {
-                        bail!("can only implement virtual functions for parent classes")
+                    if !item.virtual_.is_some() && !item.signal.is_some() {
+                        bail!(
+                            "can only implement virtual functions and signal handlers \
+                             for parent classes"
+                        )
                     }
                     if item.public.is_some() {
                         bail!("overrides are always public, no `pub` needed")
                     }
-                    let method = match class.translate_method(item)? {
+                    if item.signal.is_some() && !attrs.is_empty() {
+                        bail!(
+                            "signal `{}` is declared by the parent class, so it can't have \
+                             attributes here",
+                            item.name
+                        )
+                    }
+                    let method = match class.translate_method(item, attrs, types)? {
                         Slot::VirtualMethod(VirtualMethod {
                             sig,
                             body: Some(body),
-                        }) => Method {
-                            public: false,
+                        }) => Override {
                             sig,
                             body,
+                            is_signal: false,
                         },
                         Slot::VirtualMethod(VirtualMethod { .. }) => {
                             bail!("overrides must provide a body for virtual methods");
                         }
+                        Slot::Signal(Signal {
+                            sig,
+                            body: Some(body),
+                            ..
+                        }) => Override {
+                            sig,
+                            body,
+                            is_signal: true,
+                        },
+                        Slot::Signal(Signal { .. }) => {
+                            bail!("overrides must provide a body for signal handlers");
+                        }
                         _ => unreachable!(),
                     };
                     class
@@ -291,11 +721,11 @@ impl<'ast> Classes<'ast> {
                 for item in impl_.items.iter() {
                     match item.node {
                         ast::ImplItemKind::Prop(_) => {
-                            let property = class.translate_property(item)?;
+                            let property = class.translate_property(item, types)?;
                             class.properties.push(property);
                         }
                         _ => {
-                            let slot = class.translate_slot(item)?;
+                            let slot = class.translate_slot(item, types)?;
                             class.slots.push(slot);
                         }
                     }
@@ -304,9 +734,85 @@ impl<'ast> Classes<'ast> {
 
             ast::Impl {
                 is_interface: true,
-                trait_: Some(_parent_class),
+                trait_: Some(iface_name),
                 ..
-            } => unimplemented!(),
+            } => {
+                let iface = match interfaces.lookup(&iface_name) {
+                    Some(iface) => iface,
+                    None => bail!("interface `{}` is not defined", iface_name),
+                };
+                if class.interface_impls.contains_key(&iface_name) {
+                    bail!(
+                        "interface `{}` is implemented more than once for class `{}`",
+                        iface_name,
+                        class.name
+                    );
+                }
+
+                let mut methods: Vec<Override> = Vec::new();
+                for item in impl_.items.iter() {
+                    let item = match item.node {
+                        ast::ImplItemKind::Method(ref m) if item.attrs.is_empty() => m,
+                        ast::ImplItemKind::Method(_) => {
+                            bail!("interface methods can't have attributes");
+                        }
+                        ast::ImplItemKind::ReserveSlots(_) => {
+                            bail!("can't reserve slots in an interface impl");
+                        }
+                        ast::ImplItemKind::Prop(_) => {
+                            bail!("can't define props in an interface impl");
+                        }
+                    };
+                    if item.virtual_.is_none() {
+                        bail!(
+                            "can only implement virtual functions for interface `{}`",
+                            iface_name
+                        );
+                    }
+                    if item.public.is_some() {
+                        bail!("interface methods are always public, no `pub` needed")
+                    }
+                    if !iface.has_method(&item.name) {
+                        bail!("interface `{}` has no method `{}`", iface_name, item.name);
+                    }
+                    if methods.iter().any(|m| m.sig.name == item.name) {
+                        bail!(
+                            "duplicate method `{}` in impl of interface `{}`",
+                            item.name,
+                            iface_name
+                        );
+                    }
+                    let body = match item.body {
+                        Some(ref body) => body,
+                        None => bail!("interface impls must provide a body for `{}`", item.name),
+                    };
+                    methods.push(Override {
+                        sig: extract_sig(item, types)?,
+                        body,
+                        is_signal: false,
+                    });
+                }
+
+                // Methods without a default implementation are required
+                for slot in iface.slots.iter() {
+                    if let Slot::VirtualMethod(VirtualMethod {
+                        ref sig,
+                        body: None,
+                    }) = *slot
+                    {
+                        if !methods.iter().any(|m| m.sig.name == sig.name) {
+                            bail!(
+                                "class `{}` must implement method `{}` of interface `{}`",
+                                class.name,
+                                sig.name,
+                                iface_name
+                            );
+                        }
+                    }
+                }
+
+                class.interface_impls.insert(iface_name, methods);
+            }
 
             _ => unreachable!(),
         }
@@ -317,13 +823,144 @@ impl<'ast> Classes<'ast> {
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Class> + 'a {
         self.items.values()
     }
+
+    /// Checks that the interfaces in `class Foo: Parent, Iface` match
+    /// the `impl interface Iface for Foo` blocks, and that the
+    /// prerequisites of each interface are implemented first.
+    fn check_interfaces(&self, interfaces: &Interfaces) -> Result<()> {
+        for class in self.items.values() {
+            for iface_name in class.implements.iter() {
+                if !class.interface_impls.contains_key(iface_name) {
+                    bail!(
+                        "class `{}` implements interface `{}`, but there is no \
+                         `impl interface {} for {}`",
+                        class.name,
+                        iface_name,
+                        iface_name,
+                        class.name
+                    );
+                }
+            }
+            for iface_name in class.interface_impls.keys() {
+                if !class.implements.contains(iface_name) {
+                    bail!(
+                        "interface `{}` is implemented for class `{}`, but it is not \
+                         declared like `class {}: Parent, {}`",
+                        iface_name,
+                        class.name,
+                        class.name,
+                        iface_name
+                    );
+                }
+            }
+
+            // GObject requires the prerequisite interfaces to be added
+            // to the type before the interfaces that require them
+            for (i, iface_name) in class.implements.iter().enumerate() {
+                let iface = interfaces.lookup(iface_name).unwrap();
+                for path in iface.prerequisites.iter() {
+                    let prerequisite = match interfaces.lookup_path(path) {
+                        Some(prerequisite) => prerequisite.name,
+                        None => {
+                            // A class prerequisite must be the class itself
+                            // or one of its ancestors, if it's defined here
+                            let required_class = path_to_ident(path)
+                                .and_then(|name| self.items.get(&name))
+                                .map(|required| required.name);
+                            if let Some(required_class) = required_class {
+                                if !self.is_or_descends_from(class, &required_class) {
+                                    bail!(
+                                        "interface `{}` requires `{}`, so class `{}` must \
+                                         derive from `{}` to implement it",
+                                        iface_name,
+                                        required_class,
+                                        class.name,
+                                        required_class
+                                    );
+                                }
+                            }
+                            continue; // otherwise, a class or interface from elsewhere
+                        }
+                    };
+                    if !class.implements[..i].contains(&prerequisite)
+                        && !self.ancestor_implements(class, &prerequisite)
+                    {
+                        bail!(
+                            "interface `{}` requires `{}`, so class `{}` must implement \
+                             `{}` before it",
+                            iface_name,
+                            prerequisite,
+                            class.name,
+                            prerequisite
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether `class` is `ancestor`, or derives from it through
+    /// classes that are defined in the program.
+    fn is_or_descends_from(&self, class: &Class, ancestor: &Ident) -> bool {
+        if class.name == *ancestor {
+            return true;
+        }
+        let parent = class
+            .extends
+            .and_then(path_to_ident)
+            .and_then(|name| self.items.get(&name));
+        match parent {
+            Some(parent) => self.is_or_descends_from(parent, ancestor),
+            None => false,
+        }
+    }
+
+    /// Returns whether one of the ancestors of `class` that are defined
+    /// in the program implements `iface`.
+    fn ancestor_implements(&self, class: &Class, iface: &Ident) -> bool {
+        let parent = class
+            .extends
+            .and_then(path_to_ident)
+            .and_then(|name| self.items.get(&name));
+        match parent {
+            Some(parent) => {
+                parent.implements.contains(iface) || self.ancestor_implements(parent, iface)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Returns whether a path, as a token string, names `glib::Object`.
+fn is_gobject_path(path_string: &str) -> bool {
+    let path_string = path_string.trim_left_matches(":: ");
+    path_string == "glib :: Object" || path_string == "glib :: object :: Object"
+}
+
+/// Returns the identifier of a path like `Foo`, or None for paths like
+/// `gtk::Widget` which can't name an item in the program.
+fn path_to_ident(path: &Path) -> Option<Ident> {
+    if path.leading_colon.is_none() && path.segments.len() == 1 {
+        let segment = path.segments.first().unwrap().into_value();
+        if segment.arguments.is_empty() {
+            return Some(segment.ident);
+        }
+    }
+    None
 }
 
 impl<'ast> Class<'ast> {
-    fn translate_slot(&mut self, item: &'ast ast::ImplItem) -> Result<Slot<'ast>> {
-        assert_eq!(item.attrs.len(), 0); // attributes unimplemented
+    fn translate_slot(
+        &mut self,
+        item: &'ast ast::ImplItem,
+        types: &DeclaredTypes,
+    ) -> Result<Slot<'ast>> {
         match item.node {
-            ast::ImplItemKind::Method(ref method) => self.translate_method(method),
+            ast::ImplItemKind::Method(ref method) => {
+                self.translate_method(method, &item.attrs, types)
+            }
             ast::ImplItemKind::ReserveSlots(ref _slots) => {
                 panic!("reserve slots not implemented");
             }
@@ -331,7 +968,22 @@ impl<'ast> Class<'ast> {
         }
     }
 
-    fn translate_method(&mut self, method: &'ast ast::ImplItemMethod) -> Result<Slot<'ast>> {
+    fn translate_method(
+        &mut self,
+        method: &'ast ast::ImplItemMethod,
+        attrs: &[syn::Attribute],
+        types: &DeclaredTypes,
+    ) -> Result<Slot<'ast>> {
+        if method.signal.is_none() {
+            if let Some(attr) = attrs.first() {
+                bail!(
+                    "unknown attribute `{}` on function `{}`",
+                    attr_name(attr)?,
+                    method.name
+                );
+            }
+        }
+
         if method.signal.is_some() {
             if method.public.is_some() {
                 bail!(
@@ -347,11 +999,26 @@ impl<'ast> Class<'ast> {
                 )
             }
 
-            let sig = self.extract_sig(method)?;
+            let sig = extract_sig(method, types)?;
+            for ty in sig.arg_types().chain(Some(&sig.output)) {
+                if !ty.has_gtype() {
+                    let mut tokens = Tokens::new();
+                    ty.to_tokens(&mut tokens);
+                    bail!(
+                        "signal `{}` can't use type `{}` because it has no GType",
+                        method.name,
+                        tokens
+                    );
+                }
+            }
+
+            let (flags, accumulator) = signal_attributes(method.name, attrs, &sig)?;
+
             Ok(Slot::Signal(Signal {
-                // FIXME: signal flags
                 sig,
                 body: method.body.as_ref(),
+                flags,
+                accumulator,
             }))
         } else if method.virtual_.is_some() {
             if method.public.is_some() {
@@ -360,13 +1027,13 @@ impl<'ast> Class<'ast> {
                     method.name
                 )
             }
-            let sig = self.extract_sig(method)?;
+            let sig = extract_sig(method, types)?;
             Ok(Slot::VirtualMethod(VirtualMethod {
                 sig,
                 body: method.body.as_ref(),
             }))
         } else {
-            let sig = self.extract_sig(method)?;
+            let sig = extract_sig(method, types)?;
             Ok(Slot::Method(Method {
                 sig,
                 public: method.public.is_some(),
@@ -378,169 +1045,133 @@ impl<'ast> Class<'ast> {
         }
     }
 
-    fn extract_sig(&mut self, method: &'ast ast::ImplItemMethod) -> Result<FnSig<'ast>> {
-        Ok(FnSig {
-            output: self.extract_output(&method.output)?,
-            inputs: self.extract_inputs(&method.inputs)?,
-            name: method.name,
-        })
-    }
-
-    fn extract_output(&mut self, output: &'ast ReturnType) -> Result<Ty<'ast>> {
-        match *output {
-            ReturnType::Type(_, ref boxt) => self.extract_ty(boxt),
-            ReturnType::Default => Ok(Ty::Unit),
-        }
-    }
-
-    fn extract_inputs(
+    fn translate_property(
         &mut self,
-        punc: &'ast Punctuated<syn::FnArg, Token!(,)>,
-    ) -> Result<Vec<FnArg<'ast>>> {
-        punc.iter()
-            .map(|arg| match *arg {
-                syn::FnArg::Captured(syn::ArgCaptured {
-                    ref pat, ref ty, ..
-                }) => {
-                    let (name, mutbl) = match *pat {
-                        syn::Pat::Ident(syn::PatIdent {
-                            by_ref: None,
-                            mutability: m,
-                            ident,
-                            subpat: None,
-                        }) => (ident, m),
-                        _ => bail!("only bare identifiers are allowed as argument patterns"),
-                    };
-
-                    Ok(FnArg::Arg {
-                        mutbl,
-                        name,
-                        ty: self.extract_ty(ty)?,
-                    })
-                }
-                syn::FnArg::SelfRef(syn::ArgSelfRef {
-                    and_token,
-                    lifetime: None,
-                    mutability: None,
-                    self_token,
-                }) => Ok(FnArg::SelfRef(and_token, self_token)),
-                syn::FnArg::SelfRef(syn::ArgSelfRef {
-                    mutability: Some(..),
-                    ..
-                }) => bail!("&mut self not implemented yet"),
-                syn::FnArg::SelfRef(syn::ArgSelfRef {
-                    lifetime: Some(..), ..
-                }) => bail!("lifetime arguments on self not implemented yet"),
-                syn::FnArg::SelfValue(_) => bail!("by-value self not implemented"),
-                syn::FnArg::Inferred(_) => bail!("cannot have inferred function arguments"),
-                syn::FnArg::Ignored(_) => bail!("cannot have ignored function arguments"),
-            })
-            .collect()
-    }
-
-    fn extract_ty(&mut self, t: &'ast syn::Type) -> Result<Ty<'ast>> {
-        match *t {
-            syn::Type::Slice(_) => bail!("slice types not implemented yet"),
-            syn::Type::Array(_) => bail!("array types not implemented yet"),
-            syn::Type::Ptr(_) => bail!("ptr types not implemented yet"),
-            syn::Type::Reference(syn::TypeReference {
-                lifetime: Some(_), ..
-            }) => bail!("borrowed types with lifetimes not implemented yet"),
-            syn::Type::Reference(syn::TypeReference {
-                lifetime: None,
-                ref elem,
-                ref mutability,
-                ..
-            }) => {
-                if let Some(_) = *mutability {
-                    bail!("mutable borrowed pointers not implemented");
-                }
-                let path = match **elem {
-                    syn::Type::Path(syn::TypePath {
-                        qself: None,
-                        ref path,
-                    }) => path,
-                    _ => bail!("only borrowed pointers to paths supported"),
-                };
-                let ty = self.extract_ty_path(path)?;
-                Ok(Ty::Borrowed(Box::new(ty)))
-            }
-            syn::Type::BareFn(_) => bail!("function pointer types not implemented yet"),
-            syn::Type::Never(_) => bail!("never not implemented yet"),
-            syn::Type::Tuple(syn::TypeTuple { ref elems, .. }) => {
-                if elems.len() == 0 {
-                    Ok(Ty::Unit)
-                } else {
-                    bail!("tuple types not implemented yet")
-                }
-            }
-            syn::Type::Path(syn::TypePath { qself: Some(_), .. }) => {
-                bail!("path types with qualified self (`as` syntax) not allowed")
+        item: &'ast ast::ImplItem,
+        types: &DeclaredTypes,
+    ) -> Result<Property<'ast>> {
+        if let ast::ImplItemKind::Prop(ref prop) = item.node {
+            let name = prop.name;
+            if !param_name_is_valid(&canonical_param_name(name.as_ref())) {
+                bail!("invalid property name `{}`", name);
             }
-            syn::Type::Path(syn::TypePath {
-                qself: None,
-                ref path,
-            }) => self.extract_ty_path(path),
-            syn::Type::TraitObject(_) => bail!("trait objects not implemented yet"),
-            syn::Type::ImplTrait(_) => bail!("trait objects not implemented yet"),
-            syn::Type::Paren(syn::TypeParen { ref elem, .. }) => self.extract_ty(elem),
-            syn::Type::Group(syn::TypeGroup { ref elem, .. }) => self.extract_ty(elem),
-            syn::Type::Infer(_) => bail!("underscore types not allowed"),
-            syn::Type::Macro(_) => bail!("type macros not allowed"),
-            syn::Type::Verbatim(_) => bail!("type macros not allowed"),
-        }
-    }
-
-    fn extract_ty_path(&mut self, t: &'ast syn::Path) -> Result<Ty<'ast>> {
-        if t.segments.iter().any(|segment| match segment.arguments {
-            syn::PathArguments::None => false,
-            _ => true,
-        }) {
-            bail!("type or lifetime parameters not allowed")
-        }
-        if t.leading_colon.is_some() || t.segments.len() > 1 {
-            return Ok(Ty::Owned(t));
-        }
-
-        // let ident = t.segments.get(0).item().ident;
-        let ident = t.segments.first().unwrap().value().ident;
 
-        match ident.as_ref() {
-            "char" => Ok(Ty::Char(ident)),
-            "bool" => Ok(Ty::Bool(ident)),
-            "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
-                Ok(Ty::Integer(ident))
+            let type_ = extract_ty(&prop.type_, types)?;
+            match type_ {
+                Ty::Bool(_) | Ty::Integer(_) | Ty::Float(_) | Ty::String(_) => (),
+                Ty::Enum(_) | Ty::Flags(_) | Ty::Boxed(_) => (),
+                Ty::Option(ref t) if t.is_string() => (),
+                _ => bail!("property `{}` has unsupported type", name),
             }
-            _other => Ok(Ty::Owned(t)),
-        }
-    }
-
-    fn translate_property(&mut self, item: &'ast ast::ImplItem) -> Result<Property<'ast>> {
-        assert_eq!(item.attrs.len(), 0); // attributes unimplemented
-        if let ast::ImplItemKind::Prop(ref prop) = item.node {
-            let name = prop.name;
-            let type_ = &prop.type_;
 
             let getter = match prop.getter() {
-                Some(&ast::ImplPropBlock::Getter(ref b)) => b,
-                None => bail!("property without getter: {}", name),
+                Some(&ast::ImplPropBlock::Getter(ref b)) => Some(b),
+                None => None,
                 _ => bail!("invalid property getter: {}", name),
             };
 
             let setter = match prop.setter() {
-                Some(ast::ImplPropBlock::Setter(ref b)) => PropertySetterBlock {
+                Some(ast::ImplPropBlock::Setter(ref b)) => Some(PropertySetterBlock {
                     param: b.param,
                     body: &b.block,
-                },
-                None => bail!("property without setter: {}", name),
+                }),
+                None => None,
                 _ => bail!("invalid property setter: {}", name),
             };
 
+            if getter.is_none() && setter.is_none() {
+                bail!("property `{}` needs a getter, a setter, or both", name);
+            }
+            if prop.items.len() > getter.iter().count() + setter.iter().count() {
+                bail!(
+                    "property `{}` can only have one getter and one setter",
+                    name
+                );
+            }
+
+            let mut nick = None;
+            let mut blurb = None;
+            let mut default = None;
+            let mut minimum = None;
+            let mut maximum = None;
+            let mut construct = false;
+            let mut construct_only = false;
+            let mut constructor_arg = false;
+            let mut explicit_notify = false;
+
+            for attr in item.attrs.iter() {
+                let attr_name = attr_name(attr)?;
+                let duplicate = match attr_name.as_ref() {
+                    "nick" => mem::replace(&mut nick, Some(attr_string(attr)?)).is_some(),
+                    "blurb" => mem::replace(&mut blurb, Some(attr_string(attr)?)).is_some(),
+                    "default" => mem::replace(&mut default, Some(attr_expr(attr)?)).is_some(),
+                    "min" => mem::replace(&mut minimum, Some(attr_expr(attr)?)).is_some(),
+                    "max" => mem::replace(&mut maximum, Some(attr_expr(attr)?)).is_some(),
+                    "construct" => mem::replace(&mut construct, attr_word(attr)?),
+                    "construct_only" => mem::replace(&mut construct_only, attr_word(attr)?),
+                    "constructor_arg" => mem::replace(&mut constructor_arg, attr_word(attr)?),
+                    "explicit_notify" => mem::replace(&mut explicit_notify, attr_word(attr)?),
+                    _ => bail!("unknown attribute `{}` on property `{}`", attr_name, name),
+                };
+                if duplicate {
+                    bail!("duplicate attribute `{}` on property `{}`", attr_name, name);
+                }
+            }
+
+            if construct && construct_only {
+                bail!(
+                    "property `{}` can't be both #[construct] and #[construct_only]",
+                    name
+                );
+            }
+            if (construct || construct_only) && setter.is_none() {
+                bail!(
+                    "property `{}` is set during construction so it needs a setter",
+                    name
+                );
+            }
+            if let Ty::Boxed(_) = type_ {
+                if construct || construct_only {
+                    bail!(
+                        "boxed property `{}` has no default value, so it can't be set \
+                         during construction",
+                        name
+                    );
+                }
+            }
+            if constructor_arg && setter.is_none() {
+                bail!(
+                    "property `{}` is a constructor argument so it needs a setter",
+                    name
+                );
+            }
+            // Writable properties get a method of the same name in
+            // FooBuilder, which has its own set() and build()
+            let builder_method = lower_case_instance_name(name.as_ref());
+            if setter.is_some() && (builder_method == "set" || builder_method == "build") {
+                bail!(
+                    "writable property `{}` would clash with `{}()` in the class's builder",
+                    name,
+                    builder_method
+                );
+            }
+
+            let default = check_property_values(name, &type_, types, default, &minimum, &maximum)?;
+
             return Ok(Property {
                 name,
                 type_,
                 getter,
                 setter,
+                nick,
+                blurb,
+                default,
+                minimum,
+                maximum,
+                construct,
+                construct_only,
+                constructor_arg,
+                explicit_notify,
             });
         }
 
@@ -548,6 +1179,706 @@ impl<'ast> Class<'ast> {
     }
 }
 
+fn extract_sig<'ast>(
+    method: &'ast ast::ImplItemMethod,
+    types: &DeclaredTypes,
+) -> Result<FnSig<'ast>> {
+    Ok(FnSig {
+        output: extract_output(&method.output, types)?,
+        inputs: extract_inputs(&method.inputs, types)?,
+        name: method.name,
+    })
+}
+
+fn extract_output<'ast>(output: &'ast ReturnType, types: &DeclaredTypes) -> Result<Ty<'ast>> {
+    match *output {
+        ReturnType::Type(_, ref boxt) => match extract_ty(boxt, types)? {
+            Ty::Str(_) => bail!("cannot return `&str`; return `String` instead"),
+            Ty::Option(ref t) if t.is_borrowed() => {
+                bail!("cannot return `Option<&T>`; return `Option<T>` instead")
+            }
+            ty => Ok(ty),
+        },
+        ReturnType::Default => Ok(Ty::Unit),
+    }
+}
+
+fn extract_inputs<'ast>(
+    punc: &'ast Punctuated<syn::FnArg, Token!(,)>,
+    types: &DeclaredTypes,
+) -> Result<Vec<FnArg<'ast>>> {
+    punc.iter()
+        .map(|arg| match *arg {
+            syn::FnArg::Captured(syn::ArgCaptured {
+                ref pat, ref ty, ..
+            }) => {
+                let (name, mutbl) = match *pat {
+                    syn::Pat::Ident(syn::PatIdent {
+                        by_ref: None,
+                        mutability: m,
+                        ident,
+                        subpat: None,
+                    }) => (ident, m),
+                    _ => bail!("only bare identifiers are allowed as argument patterns"),
+                };
+
+                Ok(FnArg::Arg {
+                    mutbl,
+                    name,
+                    ty: extract_ty(ty, types)?,
+                })
+            }
+            syn::FnArg::SelfRef(syn::ArgSelfRef {
+                and_token,
+                lifetime: None,
+                mutability: None,
+                self_token,
+            }) => Ok(FnArg::SelfRef(and_token, self_token)),
+            syn::FnArg::SelfRef(syn::ArgSelfRef {
+                mutability: Some(..),
+                ..
+            }) => bail!("&mut self not implemented yet"),
+            syn::FnArg::SelfRef(syn::ArgSelfRef {
+                lifetime: Some(..), ..
+            }) => bail!("lifetime arguments on self not implemented yet"),
+            syn::FnArg::SelfValue(_) => bail!("by-value self not implemented"),
+            syn::FnArg::Inferred(_) => bail!("cannot have inferred function arguments"),
+            syn::FnArg::Ignored(_) => bail!("cannot have ignored function arguments"),
+        })
+        .collect()
+}
+
+fn extract_ty<'ast>(t: &'ast syn::Type, types: &DeclaredTypes) -> Result<Ty<'ast>> {
+    match *t {
+        syn::Type::Slice(_) => bail!("slice types not implemented yet"),
+        syn::Type::Array(_) => bail!("array types not implemented yet"),
+        syn::Type::Ptr(_) => bail!("ptr types not implemented yet"),
+        syn::Type::Reference(syn::TypeReference {
+            lifetime: Some(_), ..
+        }) => bail!("borrowed types with lifetimes not implemented yet"),
+        syn::Type::Reference(syn::TypeReference {
+            lifetime: None,
+            ref elem,
+            ref mutability,
+            ..
+        }) => {
+            if let Some(_) = *mutability {
+                bail!("mutable borrowed pointers not implemented");
+            }
+            let path = match **elem {
+                syn::Type::Path(syn::TypePath {
+                    qself: None,
+                    ref path,
+                }) => path,
+                _ => bail!("only borrowed pointers to paths supported"),
+            };
+            if path.leading_colon.is_none() && path.segments.len() == 1 {
+                let ident = path.segments.first().unwrap().value().ident;
+                if ident.as_ref() == "str" {
+                    return Ok(Ty::Str(ident));
+                }
+            }
+            match extract_ty_path(path, types)? {
+                Ty::Boxed(ident) => bail!(
+                    "boxed types are passed by value; use `{}` instead of `&{}`",
+                    ident,
+                    ident
+                ),
+                ty => Ok(Ty::Borrowed(Box::new(ty))),
+            }
+        }
+        syn::Type::BareFn(_) => bail!("function pointer types not implemented yet"),
+        syn::Type::Never(_) => bail!("never not implemented yet"),
+        syn::Type::Tuple(syn::TypeTuple { ref elems, .. }) => {
+            if elems.len() == 0 {
+                Ok(Ty::Unit)
+            } else {
+                bail!("tuple types not implemented yet")
+            }
+        }
+        syn::Type::Path(syn::TypePath { qself: Some(_), .. }) => {
+            bail!("path types with qualified self (`as` syntax) not allowed")
+        }
+        syn::Type::Path(syn::TypePath {
+            qself: None,
+            ref path,
+        }) => extract_ty_path(path, types),
+        syn::Type::TraitObject(_) => bail!("trait objects not implemented yet"),
+        syn::Type::ImplTrait(_) => bail!("trait objects not implemented yet"),
+        syn::Type::Paren(syn::TypeParen { ref elem, .. }) => extract_ty(elem, types),
+        syn::Type::Group(syn::TypeGroup { ref elem, .. }) => extract_ty(elem, types),
+        syn::Type::Infer(_) => bail!("underscore types not allowed"),
+        syn::Type::Macro(_) => bail!("type macros not allowed"),
+        syn::Type::Verbatim(_) => bail!("type macros not allowed"),
+    }
+}
+
+fn extract_ty_path<'ast>(t: &'ast syn::Path, types: &DeclaredTypes) -> Result<Ty<'ast>> {
+    if let Some(inner) = option_type_argument(t) {
+        return match extract_ty(inner, types)? {
+            ty @ Ty::Str(_) | ty @ Ty::String(_) | ty @ Ty::Owned(_) => {
+                Ok(Ty::Option(Box::new(ty)))
+            }
+            ty @ Ty::Borrowed(_) => {
+                if ty.has_gtype() {
+                    Ok(Ty::Option(Box::new(ty)))
+                } else {
+                    bail!("only strings and object types can be optional")
+                }
+            }
+            _ => bail!("only strings and object types can be optional"),
+        };
+    }
+
+    if t.segments.iter().any(|segment| match segment.arguments {
+        syn::PathArguments::None => false,
+        _ => true,
+    }) {
+        bail!("type or lifetime parameters not allowed")
+    }
+    if t.leading_colon.is_some() || t.segments.len() > 1 {
+        return Ok(Ty::Owned(t));
+    }
+
+    // let ident = t.segments.get(0).item().ident;
+    let ident = t.segments.first().unwrap().value().ident;
+
+    match ident.as_ref() {
+        "char" => Ok(Ty::Char(ident)),
+        "bool" => Ok(Ty::Bool(ident)),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            Ok(Ty::Integer(ident))
+        }
+        "f32" | "f64" => Ok(Ty::Float(ident)),
+        "String" => Ok(Ty::String(ident)),
+        _ => Ok(types.lookup(ident).unwrap_or(Ty::Owned(t))),
+    }
+}
+
+/// If `path` is `Option<T>`, returns `T`.
+fn option_type_argument(path: &syn::Path) -> Option<&syn::Type> {
+    if path.leading_colon.is_some() || path.segments.len() != 1 {
+        return None;
+    }
+
+    let segment = path.segments.first().unwrap().into_value();
+    if segment.ident.as_ref() != "Option" {
+        return None;
+    }
+
+    match segment.arguments {
+        syn::PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+            match *args.args.first().unwrap().into_value() {
+                syn::GenericArgument::Type(ref ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Checks that the `#[default]`, `#[min]` and `#[max]` attributes of
+/// a property are valid for its type, and returns the default value
+/// to use for the property's `GParamSpec`.
+///
+/// GObject requires the default value to be within the property's
+/// range, so if the user did not specify a default and zero is out of
+/// range, this picks the closest bound instead.
+fn check_property_values(
+    name: Ident,
+    type_: &Ty,
+    types: &DeclaredTypes,
+    default: Option<syn::Expr>,
+    minimum: &Option<syn::Expr>,
+    maximum: &Option<syn::Expr>,
+) -> Result<Option<syn::Expr>> {
+    match *type_ {
+        Ty::Bool(_) => {
+            if minimum.is_some() || maximum.is_some() {
+                bail!(
+                    "boolean property `{}` can't have a minimum or maximum",
+                    name
+                );
+            }
+            if let Some(ref default) = default {
+                match *default {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Bool(_),
+                        ..
+                    }) => (),
+                    _ => bail!(
+                        "default value for property `{}` must be `true` or `false`",
+                        name
+                    ),
+                }
+            }
+            Ok(default)
+        }
+
+        Ty::Integer(ref ident) => {
+            let (type_min, type_max) = integer_range(ident.as_ref());
+
+            let value = |expr: &Option<syn::Expr>, what: &str, fallback: i128| match *expr {
+                Some(ref expr) => match integer_literal_value(expr) {
+                    Some(v) if v >= type_min && v <= type_max => Ok(v),
+                    Some(_) => Err(Error::from(format!(
+                        "{} for property `{}` is out of range for `{}`",
+                        what, name, ident
+                    ))),
+                    None => Err(Error::from(format!(
+                        "{} for property `{}` must be an integer literal",
+                        what, name
+                    ))),
+                },
+                None => Ok(fallback),
+            };
+
+            let min = value(minimum, "minimum", type_min)?;
+            let max = value(maximum, "maximum", type_max)?;
+            if min > max {
+                bail!(
+                    "minimum for property `{}` is greater than its maximum",
+                    name
+                );
+            }
+
+            if default.is_some() {
+                let v = value(&default, "default value", 0)?;
+                if v < min || v > max {
+                    bail!(
+                        "default value for property `{}` is not between its minimum and maximum",
+                        name
+                    );
+                }
+                Ok(default)
+            } else if min > 0 {
+                Ok(minimum.clone())
+            } else if max < 0 {
+                Ok(maximum.clone())
+            } else {
+                Ok(None)
+            }
+        }
+
+        Ty::Float(ref ident) => {
+            let (type_min, type_max) = float_range(ident.as_ref());
+
+            let value = |expr: &Option<syn::Expr>, what: &str, fallback: f64| match *expr {
+                Some(ref expr) => match float_literal_value(expr, ident.as_ref()) {
+                    Some(v) if v >= type_min && v <= type_max => Ok(v),
+                    Some(_) => Err(Error::from(format!(
+                        "{} for property `{}` is out of range for `{}`",
+                        what, name, ident
+                    ))),
+                    None => Err(Error::from(format!(
+                        "{} for property `{}` must be a `{}` literal like `1.0`",
+                        what, name, ident
+                    ))),
+                },
+                None => Ok(fallback),
+            };
+
+            let min = value(minimum, "minimum", type_min)?;
+            let max = value(maximum, "maximum", type_max)?;
+            if min > max {
+                bail!(
+                    "minimum for property `{}` is greater than its maximum",
+                    name
+                );
+            }
+
+            if default.is_some() {
+                let v = value(&default, "default value", 0.0)?;
+                if v < min || v > max {
+                    bail!(
+                        "default value for property `{}` is not between its minimum and maximum",
+                        name
+                    );
+                }
+                Ok(default)
+            } else if min > 0.0 {
+                Ok(minimum.clone())
+            } else if max < 0.0 {
+                Ok(maximum.clone())
+            } else {
+                Ok(None)
+            }
+        }
+
+        Ty::String(_) | Ty::Option(_) => {
+            if minimum.is_some() || maximum.is_some() {
+                bail!("string property `{}` can't have a minimum or maximum", name);
+            }
+            if let Some(ref default) = default {
+                match *default {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(_),
+                        ..
+                    }) => (),
+                    _ => bail!(
+                        "default value for property `{}` must be a string literal",
+                        name
+                    ),
+                }
+            }
+            Ok(default)
+        }
+
+        Ty::Enum(ref ident) => {
+            if minimum.is_some() || maximum.is_some() {
+                bail!("enum property `{}` can't have a minimum or maximum", name);
+            }
+            let enum_ = types.enums.get(ident).unwrap();
+            if let Some(ref default) = default {
+                if !is_variant_path(default, enum_) {
+                    bail!(
+                        "default value for property `{}` must be a variant like `{}::{}`",
+                        name,
+                        ident,
+                        enum_.variants[0].name
+                    );
+                }
+                Ok(Some(default.clone()))
+            } else {
+                // GEnum properties always need a valid default
+                let first = enum_.variants[0].name;
+                Ok(Some(parse_str(&format!("{}::{}", ident, first)).unwrap()))
+            }
+        }
+
+        Ty::Flags(ref ident) => {
+            if minimum.is_some() || maximum.is_some() {
+                bail!("flags property `{}` can't have a minimum or maximum", name);
+            }
+            let flags = types.enums.get(ident).unwrap();
+            if let Some(ref default) = default {
+                if !is_flags_combination(default, flags) {
+                    bail!(
+                        "default value for property `{}` must be flags like `{}::{}`, \
+                         combined with `|`",
+                        name,
+                        ident,
+                        flags.variants[0].name
+                    );
+                }
+                Ok(Some(default.clone()))
+            } else {
+                Ok(Some(parse_str(&format!("{}::empty()", ident)).unwrap()))
+            }
+        }
+
+        Ty::Boxed(_) => {
+            if default.is_some() || minimum.is_some() || maximum.is_some() {
+                bail!(
+                    "boxed property `{}` can't have a default value, minimum or maximum",
+                    name
+                );
+            }
+            Ok(default)
+        }
+
+        _ => unreachable!(), // translate_property() rejects other types
+    }
+}
+
+/// Whether `expr` is a path like `Foo::A` for a variant of `enum_`.
+fn is_variant_path(expr: &syn::Expr, enum_: &Enum) -> bool {
+    match *expr {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None,
+            ref path,
+            ..
+        }) => {
+            path.segments.len() == 2
+                && path.segments.first().unwrap().value().ident == enum_.name
+                && enum_
+                    .variants
+                    .iter()
+                    .any(|v| v.name == path.segments.last().unwrap().value().ident)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `expr` is `Foo::empty()`, or flags of `Foo` combined with
+/// `|`, like `Foo::A | Foo::B`.
+fn is_flags_combination(expr: &syn::Expr, flags: &Enum) -> bool {
+    match *expr {
+        syn::Expr::Binary(syn::ExprBinary {
+            ref left,
+            op: syn::BinOp::BitOr(_),
+            ref right,
+            ..
+        }) => is_flags_combination(left, flags) && is_flags_combination(right, flags),
+        syn::Expr::Call(syn::ExprCall {
+            ref func, ref args, ..
+        }) => match **func {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None,
+                ref path,
+                ..
+            }) => {
+                args.is_empty()
+                    && path.segments.len() == 2
+                    && path.segments.first().unwrap().value().ident == flags.name
+                    && path.segments.last().unwrap().value().ident == "empty"
+            }
+            _ => false,
+        },
+        syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => is_flags_combination(expr, flags),
+        syn::Expr::Group(syn::ExprGroup { ref expr, .. }) => is_flags_combination(expr, flags),
+        _ => is_variant_path(expr, flags),
+    }
+}
+
+fn integer_range(ident: &str) -> (i128, i128) {
+    match ident {
+        "i8" => (i8::min_value() as i128, i8::max_value() as i128),
+        "i16" => (i16::min_value() as i128, i16::max_value() as i128),
+        "i32" => (i32::min_value() as i128, i32::max_value() as i128),
+        "i64" => (i64::min_value() as i128, i64::max_value() as i128),
+        // isize and usize are passed as C longs, which may be narrower
+        "isize" => (
+            cmp::max(isize::min_value() as i128, c_long::min_value() as i128),
+            cmp::min(isize::max_value() as i128, c_long::max_value() as i128),
+        ),
+        "u8" => (u8::min_value() as i128, u8::max_value() as i128),
+        "u16" => (u16::min_value() as i128, u16::max_value() as i128),
+        "u32" => (u32::min_value() as i128, u32::max_value() as i128),
+        "u64" => (u64::min_value() as i128, u64::max_value() as i128),
+        "usize" => (
+            0,
+            cmp::min(usize::max_value() as i128, c_ulong::max_value() as i128),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+/// Evaluates an integer literal, possibly negated, like `42` or `-1`.
+fn integer_literal_value(expr: &syn::Expr) -> Option<i128> {
+    match *expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref i),
+            ..
+        }) => Some(i.value() as i128),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ref expr,
+            ..
+        }) => integer_literal_value(expr).map(|v| -v),
+        syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => integer_literal_value(expr),
+        syn::Expr::Group(syn::ExprGroup { ref expr, .. }) => integer_literal_value(expr),
+        _ => None,
+    }
+}
+
+/// Evaluates the value of a flag, which can be an integer literal, or
+/// literals combined with `<<` and `|`, like `1 << 2` or `1 | 4`.
+fn flags_literal_value(expr: &syn::Expr) -> Option<i128> {
+    match *expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref i),
+            ..
+        }) => Some(i.value() as i128),
+        syn::Expr::Binary(syn::ExprBinary {
+            ref left,
+            op: syn::BinOp::Shl(_),
+            ref right,
+            ..
+        }) => match (flags_literal_value(left), flags_literal_value(right)) {
+            (Some(l), Some(r)) if r < 64 => l.checked_shl(r as u32),
+            _ => None,
+        },
+        syn::Expr::Binary(syn::ExprBinary {
+            ref left,
+            op: syn::BinOp::BitOr(_),
+            ref right,
+            ..
+        }) => match (flags_literal_value(left), flags_literal_value(right)) {
+            (Some(l), Some(r)) => Some(l | r),
+            _ => None,
+        },
+        syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => flags_literal_value(expr),
+        syn::Expr::Group(syn::ExprGroup { ref expr, .. }) => flags_literal_value(expr),
+        _ => None,
+    }
+}
+
+fn float_range(ident: &str) -> (f64, f64) {
+    match ident {
+        "f32" => (f32::MIN as f64, f32::MAX as f64),
+        "f64" => (f64::MIN, f64::MAX),
+        _ => unreachable!(),
+    }
+}
+
+/// Gets the value of a float literal, possibly negated.  Returns
+/// `None` for other expressions, and for literals whose suffix doesn't
+/// match `ident`, the float type of the property.
+fn float_literal_value(expr: &syn::Expr, ident: &str) -> Option<f64> {
+    match *expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(ref f),
+            ..
+        }) => match (f.suffix(), ident) {
+            (syn::FloatSuffix::None, _)
+            | (syn::FloatSuffix::F32, "f32")
+            | (syn::FloatSuffix::F64, "f64") => Some(f.value()),
+            _ => None,
+        },
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ref expr,
+            ..
+        }) => float_literal_value(expr, ident).map(|v| -v),
+        syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => float_literal_value(expr, ident),
+        syn::Expr::Group(syn::ExprGroup { ref expr, .. }) => float_literal_value(expr, ident),
+        _ => None,
+    }
+}
+
+/// Returns the name of an attribute like `#[foo]` or `#[foo(...)]`.
+fn attr_name(attr: &syn::Attribute) -> Result<Ident> {
+    match attr.path.segments.first() {
+        Some(ref segment) if attr.path.segments.len() == 1 => Ok(segment.value().ident),
+        _ => {
+            let mut tokens = Tokens::new();
+            attr.path.to_tokens(&mut tokens);
+            bail!("unknown attribute `{}`", tokens)
+        }
+    }
+}
+
+/// Checks that an attribute is just a word like `#[foo]`.  Returns
+/// `true` so that callers can use it as a flag.
+fn attr_word(attr: &syn::Attribute) -> Result<bool> {
+    match attr.interpret_meta() {
+        Some(syn::Meta::Word(_)) => Ok(true),
+        _ => bail!("`#[{}]` doesn't take any arguments", attr_name(attr)?),
+    }
+}
+
+/// Computes the flags and the accumulator of a signal from its
+/// attributes, and checks them against the rules of `g_signal_newv()`.
+fn signal_attributes(
+    name: Ident,
+    attrs: &[syn::Attribute],
+    sig: &FnSig,
+) -> Result<(SignalFlags, Option<Accumulator>)> {
+    let mut flags = SignalFlags::default();
+    let mut accumulator = None;
+
+    for attr in attrs {
+        let attr_name = attr_name(attr)?;
+        let duplicate = match attr_name.as_ref() {
+            "run_first" => mem::replace(&mut flags.run_first, attr_word(attr)?),
+            "run_last" => mem::replace(&mut flags.run_last, attr_word(attr)?),
+            "run_cleanup" => mem::replace(&mut flags.run_cleanup, attr_word(attr)?),
+            "no_recurse" => mem::replace(&mut flags.no_recurse, attr_word(attr)?),
+            "detailed" => mem::replace(&mut flags.detailed, attr_word(attr)?),
+            "action" => mem::replace(&mut flags.action, attr_word(attr)?),
+            "no_hooks" => mem::replace(&mut flags.no_hooks, attr_word(attr)?),
+            "deprecated" => mem::replace(&mut flags.deprecated, attr_word(attr)?),
+            "accumulator" => {
+                mem::replace(&mut accumulator, Some(attr_accumulator(attr)?)).is_some()
+            }
+            _ => bail!("unknown attribute `{}` on signal `{}`", attr_name, name),
+        };
+        if duplicate {
+            bail!("duplicate attribute `{}` on signal `{}`", attr_name, name);
+        }
+    }
+
+    if !flags.run_first && !flags.run_last && !flags.run_cleanup {
+        flags.run_last = true;
+    }
+
+    // The return value of a signal comes from its last handler, so the
+    // class handler must run in the RUN_LAST stage to have a say in it.
+    match sig.output {
+        Ty::Unit => (),
+        _ => {
+            if !flags.run_last {
+                bail!(
+                    "signal `{}` has a return value, so it must be #[run_last]",
+                    name
+                );
+            }
+        }
+    }
+
+    match (&accumulator, &sig.output) {
+        (&None, _) => (),
+        (&Some(_), &Ty::Unit) => bail!(
+            "signal `{}` has no return value, so it can't have an accumulator",
+            name
+        ),
+        (&Some(Accumulator::FirstWins), _) => (),
+        (&Some(Accumulator::TrueHandled), &Ty::Bool(_)) => (),
+        (&Some(Accumulator::TrueHandled), _) => bail!(
+            "signal `{}` must return `bool` to use the `true_handled` accumulator",
+            name
+        ),
+
+        // Before the first handler runs, the accumulated value is the
+        // GValue's zero value, which these types can't represent.
+        (&Some(Accumulator::Custom(_)), &Ty::Owned(_))
+        | (&Some(Accumulator::Custom(_)), &Ty::Enum(_))
+        | (&Some(Accumulator::Custom(_)), &Ty::Boxed(_)) => bail!(
+            "signal `{}` can't use a custom accumulator with its return type",
+            name
+        ),
+        (&Some(Accumulator::Custom(_)), _) => (),
+    }
+
+    Ok((flags, accumulator))
+}
+
+/// Extracts the accumulator from `#[accumulator(first_wins)]`,
+/// `#[accumulator(true_handled)]` or `#[accumulator(path::to::function)]`.
+fn attr_accumulator(attr: &syn::Attribute) -> Result<Accumulator> {
+    let path = match attr_expr(attr)? {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => bail!("expected a function name in `#[accumulator(...)]`"),
+    };
+
+    if path.leading_colon.is_none() && path.segments.len() == 1 {
+        match path.segments.first().unwrap().value().ident.as_ref() {
+            "first_wins" => return Ok(Accumulator::FirstWins),
+            "true_handled" => return Ok(Accumulator::TrueHandled),
+            _ => (),
+        }
+    }
+
+    Ok(Accumulator::Custom(path))
+}
+
+/// Extracts the string from an attribute like `#[foo = "bar"]`.
+fn attr_string(attr: &syn::Attribute) -> Result<String> {
+    match attr.interpret_meta() {
+        Some(syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(ref s),
+            ..
+        })) => Ok(s.value()),
+        _ => bail!("expected a string in `#[{} = \"...\"]`", attr_name(attr)?),
+    }
+}
+
+/// Extracts the expression from an attribute like `#[foo(expr)]`.
+fn attr_expr(attr: &syn::Attribute) -> Result<syn::Expr> {
+    let mut tts = attr.tts.clone().into_iter();
+    match (tts.next(), tts.next()) {
+        (Some(TokenTree::Group(ref group)), None)
+            if group.delimiter() == Delimiter::Parenthesis =>
+        {
+            Ok(syn::parse2(group.stream())?)
+        }
+        _ => bail!("expected an expression in `#[{}(...)]`", attr_name(attr)?),
+    }
+}
+
 impl<'ast> Interfaces<'ast> {
     fn new() -> Interfaces<'ast> {
         Interfaces {
@@ -563,12 +1894,117 @@ impl<'ast> Interfaces<'ast> {
         self.items.iter().find(|c| c.1.name == name).unwrap().1
     }
 
-    fn add(&mut self, ast_iface: &'ast ast::Interface) -> Result<()> {
+    fn lookup(&self, name: &Ident) -> Option<&Interface<'ast>> {
+        self.items.get(name)
+    }
+
+    fn lookup_path(&self, path: &Path) -> Option<&Interface<'ast>> {
+        path_to_ident(path).and_then(|name| self.lookup(&name))
+    }
+
+    /// Checks that interfaces in the program don't require each other
+    /// in a cycle, since registering them would never finish.
+    fn check_prerequisites(&self) -> Result<()> {
+        for iface in self.items.values() {
+            let mut pending: Vec<&Interface> = vec![iface];
+            let mut seen: Vec<Ident> = Vec::new();
+            while let Some(current) = pending.pop() {
+                for path in current.prerequisites.iter() {
+                    if let Some(prerequisite) = self.lookup_path(path) {
+                        if prerequisite.name == iface.name {
+                            bail!("interface `{}` requires itself", iface.name);
+                        }
+                        if !seen.contains(&prerequisite.name) {
+                            seen.push(prerequisite.name);
+                            pending.push(prerequisite);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, ast_iface: &'ast ast::Interface, types: &DeclaredTypes) -> Result<()> {
+        let name = ast_iface.name;
+        let mut slots = Vec::new();
+
+        for item in ast_iface.items.iter() {
+            if let Some(attr) = item.attrs.first() {
+                bail!(
+                    "unknown attribute `{}` in interface `{}`",
+                    attr_name(attr)?,
+                    name
+                );
+            }
+
+            let method = match item.node {
+                ast::ImplItemKind::Method(ref m) => m,
+                ast::ImplItemKind::ReserveSlots(_) => {
+                    bail!("reserve slots not implemented for interfaces");
+                }
+                ast::ImplItemKind::Prop(_) => {
+                    bail!("properties not implemented for interfaces");
+                }
+            };
+            if method.signal.is_some() {
+                bail!("signals not implemented for interfaces");
+            }
+            if method.virtual_.is_none() {
+                bail!(
+                    "interface `{}` can only have virtual methods; make `{}` virtual",
+                    name,
+                    method.name
+                );
+            }
+            if method.public.is_some() {
+                bail!(
+                    "function `{}` is virtual so it doesn't need to be public",
+                    method.name
+                );
+            }
+            if slots.iter().any(|slot| match *slot {
+                Slot::VirtualMethod(ref m) => m.sig.name == method.name,
+                _ => false,
+            }) {
+                bail!("duplicate method `{}` in interface `{}`", method.name, name);
+            }
+
+            // A body is the default implementation, which classes that
+            // implement the interface don't need to override
+            slots.push(Slot::VirtualMethod(VirtualMethod {
+                sig: extract_sig(method, types)?,
+                body: method.body.as_ref(),
+            }));
+        }
+
+        let mut prerequisites: Vec<&Path> = Vec::new();
+        for path in ast_iface.requires.iter() {
+            let path_string = path.into_tokens().to_string();
+            // Every interface requires GObject if nothing else, and the
+            // wrapper is already IsA<glib::Object>, so leave it out
+            if is_gobject_path(&path_string) {
+                continue;
+            }
+            if prerequisites
+                .iter()
+                .any(|p| p.into_tokens().to_string() == path_string)
+            {
+                bail!(
+                    "prerequisite `{}` is listed more than once for interface `{}`",
+                    path.into_tokens(),
+                    name
+                );
+            }
+            prerequisites.push(path);
+        }
+
         let prev = self.items.insert(
-            ast_iface.name,
+            name,
             Interface {
-                name: ast_iface.name,
-                slots: Vec::new(),
+                name,
+                prerequisites,
+                slots,
             },
         );
         if prev.is_some() {
@@ -582,6 +2018,15 @@ impl<'ast> Interfaces<'ast> {
     }
 }
 
+impl<'ast> Interface<'ast> {
+    fn has_method(&self, name: &Ident) -> bool {
+        self.slots.iter().any(|slot| match *slot {
+            Slot::VirtualMethod(ref m) => m.sig.name == *name,
+            _ => false,
+        })
+    }
+}
+
 fn make_path_glib_object() -> Path {
     let tokens = quote_cs! { glib::Object };
     let token_stream = TokenStream::from(tokens);
@@ -621,10 +2066,18 @@ impl<'a> ToTokens for Ty<'a> {
             Ty::Char(tok) => tok.to_tokens(tokens),
             Ty::Bool(tok) => tok.to_tokens(tokens),
             Ty::Integer(t) => t.to_tokens(tokens),
+            Ty::Float(t) => t.to_tokens(tokens),
             Ty::Borrowed(ref t) => {
                 Token!(&)([Span::call_site()]).to_tokens(tokens);
                 t.to_tokens(tokens)
             }
+            Ty::Str(t) => {
+                Token!(&)([Span::call_site()]).to_tokens(tokens);
+                t.to_tokens(tokens)
+            }
+            Ty::String(t) => t.to_tokens(tokens),
+            Ty::Option(ref t) => (quote_cs! { Option<#t> }).to_tokens(tokens),
+            Ty::Enum(t) | Ty::Flags(t) | Ty::Boxed(t) => t.to_tokens(tokens),
             Ty::Owned(t) => t.to_tokens(tokens),
         }
     }
@@ -637,6 +2090,69 @@ pub mod tests {
         creates_trivial_class();
         creates_class_with_superclass();
         maps_ty_to_gtype();
+        checks_property_attributes();
+        checks_property_modes();
+        checks_string_types();
+        checks_signal_types();
+        checks_signal_flags();
+        checks_signal_accumulators();
+        checks_overrides();
+        checks_interfaces();
+        checks_optional_types();
+        checks_enums();
+        checks_flags();
+        checks_boxed();
+    }
+
+    fn program_is_ok(raw: &str) -> bool {
+        let token_stream = raw.parse::<TokenStream>().unwrap();
+        let buffer = TokenBuffer::new(token_stream);
+        let cursor = buffer.begin();
+        let ast_program = ast::Program::parse(cursor).unwrap().0;
+
+        Program::from_ast_program(&ast_program).is_ok()
+    }
+
+    /// Checks that `raw` fails to compile with an error that contains
+    /// `message`, so that tests don't pass because of some other error.
+    fn assert_program_error(raw: &str, message: &str) {
+        let token_stream = raw.parse::<TokenStream>().unwrap();
+        let buffer = TokenBuffer::new(token_stream);
+        let cursor = buffer.begin();
+        let ast_program = ast::Program::parse(cursor).unwrap().0;
+
+        match Program::from_ast_program(&ast_program) {
+            Ok(_) => panic!("expected error \"{}\" for program:\n{}", message, raw),
+            Err(e) => assert!(
+                e.to_string().contains(message),
+                "expected error \"{}\", got \"{}\" for program:\n{}",
+                message,
+                e,
+                raw
+            ),
+        }
+    }
+
+    fn class_with_property(attrs: &str, ty: &str) -> String {
+        class_with_property_blocks(
+            attrs,
+            ty,
+            "get(&self) -> T { Default::default() }
+             set(&self, value: T) { }",
+        )
+    }
+
+    fn class_with_property_blocks(attrs: &str, ty: &str, blocks: &str) -> String {
+        format!(
+            "class Foo {{}}
+             impl Foo {{
+                 {}
+                 property prop: T where T: {} {{
+                     {}
+                 }}
+             }}",
+            attrs, ty, blocks
+        )
     }
 
     fn test_class_and_superclass(raw: &str, class_name: &str, superclass_name: &str) {
@@ -683,7 +2199,10 @@ pub mod tests {
             Ty::Integer(Ident::new("i8", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_CHAR"
         );
-        // assert_eq!(Ty::Integer(Ident::new("i16", Span::call_site())).to_gtype(), ...);
+        assert_eq!(
+            Ty::Integer(Ident::new("i16", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_INT"
+        );
         assert_eq!(
             Ty::Integer(Ident::new("i32", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_INT"
@@ -692,13 +2211,19 @@ pub mod tests {
             Ty::Integer(Ident::new("i64", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_INT64"
         );
-        // assert_eq!(Ty::Integer(Ident::new("isize", Span::call_site())).to_gtype_string(), ...);
+        assert_eq!(
+            Ty::Integer(Ident::new("isize", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_LONG"
+        );
 
         assert_eq!(
             Ty::Integer(Ident::new("u8", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_UCHAR"
         );
-        // assert_eq!(Ty::Integer(Ident::new("u16", Span::call_site())).to_gtype_string(), ...);
+        assert_eq!(
+            Ty::Integer(Ident::new("u16", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_UINT"
+        );
         assert_eq!(
             Ty::Integer(Ident::new("u32", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_UINT"
@@ -707,8 +2232,994 @@ pub mod tests {
             Ty::Integer(Ident::new("u64", Span::call_site())).to_gtype_string(),
             "gobject_sys::G_TYPE_UINT64"
         );
-        // assert_eq!(Ty::Integer(Ident::new("usize", Span::call_site())).to_gtype_string(), ...);
+        assert_eq!(
+            Ty::Integer(Ident::new("usize", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_ULONG"
+        );
+
+        assert_eq!(
+            Ty::Float(Ident::new("f32", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_FLOAT"
+        );
+        assert_eq!(
+            Ty::Float(Ident::new("f64", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_DOUBLE"
+        );
+
+        assert_eq!(
+            Ty::Str(Ident::new("str", Span::call_site())).to_gtype_string(),
+            "gobject_sys::G_TYPE_STRING"
+        );
+        assert_eq!(
+            Ty::Option(Box::new(Ty::String(Ident::new(
+                "String",
+                Span::call_site()
+            ))))
+            .to_gtype_string(),
+            "gobject_sys::G_TYPE_STRING"
+        );
 
         // assert_eq!(Ty::Owned(...).to_gtype_string(), ...);
     }
+
+    fn checks_property_attributes() {
+        assert!(program_is_ok(&class_with_property(
+            "#[nick = \"Prop\"] #[blurb = \"A property\"] #[default(-1)] #[min(-5)] #[max(5)]",
+            "i32"
+        )));
+        assert!(program_is_ok(&class_with_property(
+            "#[default(true)]",
+            "bool"
+        )));
+
+        assert_program_error(
+            &class_with_property("#[foo]", "u32"),
+            "unknown attribute `foo` on property `prop`",
+        );
+        assert_program_error(
+            &class_with_property("#[nick(\"Prop\")]", "u32"),
+            "expected a string in `#[nick = \"...\"]`",
+        );
+        assert_program_error(
+            &class_with_property("#[default(1)] #[default(2)]", "u32"),
+            "duplicate attribute `default` on property `prop`",
+        );
+        assert_program_error(
+            &class_with_property("#[default(-1)]", "u32"),
+            "default value for property `prop` is out of range for `u32`",
+        );
+        assert_program_error(
+            &class_with_property("#[max(256)]", "u8"),
+            "maximum for property `prop` is out of range for `u8`",
+        );
+        assert_program_error(
+            &class_with_property("#[min(5)] #[max(4)]", "u32"),
+            "minimum for property `prop` is greater than its maximum",
+        );
+        assert_program_error(
+            &class_with_property("#[default(6)] #[max(5)]", "u32"),
+            "default value for property `prop` is not between its minimum and maximum",
+        );
+        assert_program_error(
+            &class_with_property("#[default(1)]", "bool"),
+            "default value for property `prop` must be `true` or `false`",
+        );
+        assert_program_error(
+            &class_with_property("#[min(0)]", "bool"),
+            "boolean property `prop` can't have a minimum or maximum",
+        );
+
+        assert!(program_is_ok(&class_with_property(
+            "#[default(0.5)] #[min(-1.0)] #[max(1.0f64)]",
+            "f64"
+        )));
+        assert_program_error(
+            &class_with_property("#[default(1)]", "f32"),
+            "default value for property `prop` must be a `f32` literal like `1.0`",
+        );
+        assert_program_error(
+            &class_with_property("#[default(1.0f64)]", "f32"),
+            "default value for property `prop` must be a `f32` literal like `1.0`",
+        );
+        assert_program_error(
+            &class_with_property("#[max(1e39)]", "f32"),
+            "maximum for property `prop` is out of range for `f32`",
+        );
+        assert_program_error(
+            &class_with_property("#[min(2.0)] #[max(1.0)]", "f64"),
+            "minimum for property `prop` is greater than its maximum",
+        );
+
+        assert!(program_is_ok(&class_with_property(
+            "#[default(\"foo\")]",
+            "String"
+        )));
+        assert!(program_is_ok(&class_with_property("", "Option<String>")));
+        assert_program_error(
+            &class_with_property("#[default(1)]", "String"),
+            "default value for property `prop` must be a string literal",
+        );
+        assert_program_error(
+            &class_with_property("#[min(\"a\")]", "String"),
+            "string property `prop` can't have a minimum or maximum",
+        );
+        assert_program_error(
+            &class_with_property("", "Option<u32>"),
+            "only strings and object types can be optional",
+        );
+        assert_program_error(
+            &class_with_property("", "Option<glib::Object>"),
+            "property `prop` has unsupported type",
+        );
+    }
+
+    fn checks_string_types() {
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self, a: &str, b: String, c: Option<String>) -> Option<String> {
+                     None
+                 }
+                 signal fn bar(&self, a: &str) -> String;
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self) -> &str {
+                     \"foo\"
+                 }
+             }",
+            "cannot return `&str`; return `String` instead",
+        );
+    }
+
+    fn checks_signal_types() {
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 signal fn foo(&self, a: i16, b: u16, c: isize, d: usize) -> usize;
+             }"
+        ));
+
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 signal fn foo(&self, a: &Foo, b: glib::Object) -> Foo;
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 signal fn foo(&self, a: &u32);
+             }",
+            "signal `foo` can't use type `& u32` because it has no GType",
+        );
+    }
+
+    fn checks_signal_flags() {
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 #[run_first] #[no_recurse] #[detailed] #[action] #[no_hooks] #[deprecated]
+                 signal fn foo(&self);
+
+                 #[run_cleanup]
+                 signal fn bar(&self, a: u32);
+
+                 #[run_first] #[run_last]
+                 signal fn baz(&self) -> u32;
+
+                 signal fn quux(&self) -> u32;
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[run_first]
+                 signal fn foo(&self) -> u32;
+             }",
+            "signal `foo` has a return value, so it must be #[run_last]",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[run_cleanup]
+                 signal fn foo(&self) -> u32;
+             }",
+            "signal `foo` has a return value, so it must be #[run_last]",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[run_first] #[run_first]
+                 signal fn foo(&self);
+             }",
+            "duplicate attribute `run_first` on signal `foo`",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[action(true)]
+                 signal fn foo(&self);
+             }",
+            "`#[action]` doesn't take any arguments",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[run_last]
+                 pub fn foo(&self) {}
+             }",
+            "unknown attribute `run_last` on function `foo`",
+        );
+    }
+
+    fn checks_signal_accumulators() {
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator(first_wins)]
+                 signal fn foo(&self) -> Option<String>;
+
+                 #[accumulator(true_handled)]
+                 signal fn bar(&self) -> bool;
+
+                 #[accumulator(my_accumulator)]
+                 signal fn baz(&self, a: u32) -> u32;
+
+                 #[accumulator(accumulators::sum)]
+                 signal fn quux(&self) -> Option<Foo>;
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator(first_wins)]
+                 signal fn foo(&self);
+             }",
+            "signal `foo` has no return value, so it can't have an accumulator",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator(true_handled)]
+                 signal fn foo(&self) -> u32;
+             }",
+            "signal `foo` must return `bool` to use the `true_handled` accumulator",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator(my_accumulator)]
+                 signal fn foo(&self) -> Foo;
+             }",
+            "signal `foo` can't use a custom accumulator with its return type",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator(first_wins)]
+                 #[accumulator(true_handled)]
+                 signal fn foo(&self) -> bool;
+             }",
+            "duplicate attribute `accumulator` on signal `foo`",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 #[accumulator]
+                 signal fn foo(&self) -> bool;
+             }",
+            "expected an expression in `#[accumulator(...)]`",
+        );
+    }
+
+    fn checks_overrides() {
+        assert!(program_is_ok(
+            "class Foo: Bar {}
+             impl Bar for Foo {
+                 virtual fn foo(&self, a: u32) -> u32 { a }
+                 signal fn bar(&self, a: &str) -> String { a.to_string() }
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo: Bar {}
+             impl Bar for Foo {
+                 signal fn bar(&self);
+             }",
+            "overrides must provide a body for signal handlers",
+        );
+
+        assert_program_error(
+            "class Foo: Bar {}
+             impl Bar for Foo {
+                 #[run_first]
+                 signal fn bar(&self) {}
+             }",
+            "signal `bar` is declared by the parent class, so it can't have attributes here",
+        );
+
+        assert_program_error(
+            "class Foo: Bar {}
+             impl Bar for Foo {
+                 fn foo(&self) {}
+             }",
+            "can only implement virtual functions and signal handlers for parent classes",
+        );
+    }
+
+    fn checks_interfaces() {
+        assert!(program_is_ok(
+            "interface Foo {
+                 virtual fn foo(&self, a: &str) -> u32;
+                 virtual fn bar(&self) -> String {
+                     String::new()
+                 }
+             }"
+        ));
+
+        assert_program_error(
+            "interface Foo {
+                 fn foo(&self);
+             }",
+            "interface `Foo` can only have virtual methods; make `foo` virtual",
+        );
+
+        assert_program_error(
+            "interface Foo {
+                 pub fn foo(&self) {}
+             }",
+            "interface `Foo` can only have virtual methods; make `foo` virtual",
+        );
+
+        assert_program_error(
+            "interface Foo {
+                 signal fn foo(&self);
+             }",
+            "signals not implemented for interfaces",
+        );
+
+        assert_program_error(
+            "interface Foo {
+                 virtual fn foo(&self);
+                 virtual fn foo(&self, a: u32);
+             }",
+            "duplicate method `foo` in interface `Foo`",
+        );
+
+        assert_program_error(
+            "interface Foo {
+                 virtual fn foo(&self) -> &str;
+             }",
+            "cannot return `&str`; return `String` instead",
+        );
+
+        let iface = "interface Frob {
+                         virtual fn frob(&self);
+                         virtual fn frobs(&self) -> u32 {
+                             1
+                         }
+                     }
+                     class Foo: Frob {}";
+
+        assert!(program_is_ok(&format!(
+            "{}
+             impl interface Frob for Foo {{
+                 virtual fn frob(&self) {{}}
+             }}",
+            iface
+        )));
+
+        assert!(program_is_ok(&format!(
+            "{}
+             impl interface Frob for Foo {{
+                 virtual fn frob(&self) {{}}
+                 virtual fn frobs(&self) -> u32 {{
+                     2
+                 }}
+             }}",
+            iface
+        )));
+
+        // missing required method
+        assert_program_error(
+            &format!(
+                "{}
+                 impl interface Frob for Foo {{
+                     virtual fn frobs(&self) -> u32 {{
+                         2
+                     }}
+                 }}",
+                iface
+            ),
+            "class `Foo` must implement method `frob` of interface `Frob`",
+        );
+
+        // method not in the interface
+        assert_program_error(
+            &format!(
+                "{}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                     virtual fn bar(&self) {{}}
+                 }}",
+                iface
+            ),
+            "interface `Frob` has no method `bar`",
+        );
+
+        assert_program_error(
+            &format!(
+                "{}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self);
+                 }}",
+                iface
+            ),
+            "interface impls must provide a body for `frob`",
+        );
+
+        assert_program_error(
+            &format!(
+                "{}
+                 impl interface Frob for Foo {{
+                     fn frob(&self) {{}}
+                 }}",
+                iface
+            ),
+            "can only implement virtual functions for interface `Frob`",
+        );
+
+        assert_program_error(
+            &format!(
+                "{}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                iface
+            ),
+            "interface `Frob` is implemented more than once for class `Foo`",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl interface Frob for Foo {
+                 virtual fn frob(&self) {}
+             }",
+            "interface `Frob` is not defined",
+        );
+
+        let ifaces = "interface Frob {
+                          virtual fn frob(&self);
+                      }
+                      interface Blah: Frob + gio::Icon {
+                          virtual fn blah(&self);
+                      }";
+
+        assert!(program_is_ok(&format!(
+            "{}
+             class Foo: Frob, Blah {{}}
+             impl interface Frob for Foo {{
+                 virtual fn frob(&self) {{}}
+             }}
+             impl interface Blah for Foo {{
+                 virtual fn blah(&self) {{}}
+             }}",
+            ifaces
+        )));
+
+        // the parent class implements the prerequisite
+        assert!(program_is_ok(&format!(
+            "{}
+             class Foo: Frob {{}}
+             impl interface Frob for Foo {{
+                 virtual fn frob(&self) {{}}
+             }}
+             class Bar: Foo, Blah {{}}
+             impl interface Blah for Bar {{
+                 virtual fn blah(&self) {{}}
+             }}",
+            ifaces
+        )));
+
+        // the prerequisite must be implemented first
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo: Blah, Frob {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}
+                 impl interface Blah for Foo {{
+                     virtual fn blah(&self) {{}}
+                 }}",
+                ifaces
+            ),
+            "interface `Blah` requires `Frob`, so class `Foo` must implement `Frob` before it",
+        );
+
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo: Blah {{}}
+                 impl interface Blah for Foo {{
+                     virtual fn blah(&self) {{}}
+                 }}",
+                ifaces
+            ),
+            "interface `Blah` requires `Frob`, so class `Foo` must implement `Frob` before it",
+        );
+
+        // missing impl
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo: Frob, Blah {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                ifaces
+            ),
+            "class `Foo` implements interface `Blah`, but there is no `impl interface Blah for Foo`",
+        );
+
+        // impl of an interface that the class doesn't list
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo: glib::Object {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                ifaces
+            ),
+            "interface `Frob` is implemented for class `Foo`, but it is not declared like `class Foo: Parent, Frob`",
+        );
+
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo: Frob, Frob {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                ifaces
+            ),
+            "interface `Frob` is listed more than once for class `Foo`",
+        );
+
+        assert_program_error(
+            "class Foo: glib::Object, gio::Icon {}",
+            "`gio :: Icon` is not an interface defined in gobject_gen!",
+        );
+
+        // Either interface can be reported, depending on the lookup order
+        assert_program_error(
+            "interface Foo: Bar {
+                 virtual fn foo(&self);
+             }
+             interface Bar: Foo {
+                 virtual fn bar(&self);
+             }",
+            "` requires itself",
+        );
+
+        assert_program_error(
+            "interface Foo: Bar + Bar {
+                 virtual fn foo(&self);
+             }",
+            "prerequisite `Bar` is listed more than once for interface `Foo`",
+        );
+
+        // glib::Object is implied, so it isn't kept as a prerequisite
+        let raw = "interface Foo: glib::Object + Bar {
+                       virtual fn foo(&self);
+                   }
+                   interface Bar: ::glib::object::Object {
+                       virtual fn bar(&self);
+                   }";
+        let token_stream = raw.parse::<TokenStream>().unwrap();
+        let buffer = TokenBuffer::new(token_stream);
+        let ast_program = ast::Program::parse(buffer.begin()).unwrap().0;
+        let program = Program::from_ast_program(&ast_program).unwrap();
+        let prerequisites = &program.interfaces.get("Foo").prerequisites;
+        assert_eq!(prerequisites.len(), 1);
+        assert_eq!(prerequisites[0].into_tokens().to_string(), "Bar");
+        assert!(program.interfaces.get("Bar").prerequisites.is_empty());
+
+        // a class prerequisite defined here must be an ancestor of the
+        // implementing class
+        let class_prerequisite = "class Other {}
+                                  interface Frob: Frobber {
+                                      virtual fn frob(&self);
+                                  }";
+        assert!(program_is_ok(&format!(
+            "{}
+             class Frobber {{}}
+             class Foo: Frobber, Frob {{}}
+             impl interface Frob for Foo {{
+                 virtual fn frob(&self) {{}}
+             }}",
+            class_prerequisite
+        )));
+        assert!(program_is_ok(&format!(
+            "{}
+             class Frobber: Frob {{}}
+             impl interface Frob for Frobber {{
+                 virtual fn frob(&self) {{}}
+             }}",
+            class_prerequisite
+        )));
+        assert_program_error(
+            &format!(
+                "{}
+                 class Frobber {{}}
+                 class Foo: Other, Frob {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                class_prerequisite
+            ),
+            "interface `Frob` requires `Frobber`, so class `Foo` must derive from `Frobber` to implement it",
+        );
+        assert_program_error(
+            &format!(
+                "{}
+                 class Frobber {{}}
+                 class Foo: Frob {{}}
+                 impl interface Frob for Foo {{
+                     virtual fn frob(&self) {{}}
+                 }}",
+                class_prerequisite
+            ),
+            "interface `Frob` requires `Frobber`, so class `Foo` must derive from `Frobber` to implement it",
+        );
+    }
+
+    fn checks_optional_types() {
+        assert!(program_is_ok(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self, a: Option<&str>, b: Option<&Foo>, c: Option<Foo>) -> Option<Foo> {
+                     None
+                 }
+                 signal fn bar(&self, a: Option<&str>, b: Option<&Foo>) -> Option<glib::Object>;
+             }"
+        ));
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self) -> Option<&Foo> {
+                     None
+                 }
+             }",
+            "cannot return `Option<&T>`; return `Option<T>` instead",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self, a: Option<u32>) {
+                 }
+             }",
+            "only strings and object types can be optional",
+        );
+
+        assert_program_error(
+            "class Foo {}
+             impl Foo {
+                 pub fn foo(&self, a: Option<&u32>) {
+                 }
+             }",
+            "only strings and object types can be optional",
+        );
+    }
+
+    fn checks_enums() {
+        let enum_ = "enum State { Idle, Running = 5, Done }";
+
+        assert!(program_is_ok(&format!(
+            "{}
+             class Foo {{}}
+             impl Foo {{
+                 signal fn changed(&self, state: State) -> State;
+                 pub fn get_state(&self) -> State {{
+                     State::Idle
+                 }}
+             }}",
+            enum_
+        )));
+        assert!(program_is_ok(&format!(
+            "{} {}",
+            enum_,
+            class_with_property("#[default(State::Done)]", "State")
+        )));
+
+        assert_program_error(
+            &format!(
+                "{} {}",
+                enum_,
+                class_with_property("#[default(State::Paused)]", "State")
+            ),
+            "default value for property `prop` must be a variant like `State::Idle`",
+        );
+        assert_program_error(
+            &format!(
+                "{} {}",
+                enum_,
+                class_with_property("#[default(0)]", "State")
+            ),
+            "default value for property `prop` must be a variant like `State::Idle`",
+        );
+        assert_program_error(
+            &format!(
+                "{} {}",
+                enum_,
+                class_with_property("#[min(State::Idle)]", "State")
+            ),
+            "enum property `prop` can't have a minimum or maximum",
+        );
+
+        assert_program_error(
+            "enum State {}",
+            "enum `State` must have at least one variant",
+        );
+        assert_program_error(
+            "enum State { Idle = 1, Running = 1 }",
+            "duplicate value 1 for `State::Running`",
+        );
+        assert_program_error(
+            "enum State { Idle, Running(u32) }",
+            "variant `State::Running` can't have fields",
+        );
+        assert_program_error(
+            "enum State { Idle = 1 << 2 }",
+            "value of `State::Idle` must be an integer literal",
+        );
+        assert_program_error(
+            "enum State { Idle = 4294967296 }",
+            "value of `State::Idle` is out of range for `i32`",
+        );
+        assert_program_error(
+            "enum State { Idle } enum State { Running }",
+            "redefinition of enum `State`",
+        );
+    }
+
+    fn checks_flags() {
+        let flags = "flags Mode { Read = 1 << 0, Write = 1 << 1, ReadWrite = 1 | 2 }";
+
+        assert!(program_is_ok(&format!(
+            "{}
+             class Foo {{}}
+             impl Foo {{
+                 signal fn changed(&self, mode: Mode) -> Mode;
+                 pub fn get_mode(&self) -> Mode {{
+                     Mode::Read | Mode::Write
+                 }}
+             }}",
+            flags
+        )));
+        assert!(program_is_ok(&format!(
+            "{} {}",
+            flags,
+            class_with_property("", "Mode")
+        )));
+        assert!(program_is_ok(&format!(
+            "{} {}",
+            flags,
+            class_with_property("#[default(Mode::Read | (Mode::Write))]", "Mode")
+        )));
+        assert!(program_is_ok(&format!(
+            "{} {}",
+            flags,
+            class_with_property("#[default(Mode::empty())]", "Mode")
+        )));
+
+        assert_program_error(
+            &format!(
+                "{} {}",
+                flags,
+                class_with_property("#[default(Mode::Read & Mode::Write)]", "Mode")
+            ),
+            "default value for property `prop` must be flags like `Mode::Read`, combined with `|`",
+        );
+        assert_program_error(
+            &format!(
+                "{} {}",
+                flags,
+                class_with_property("#[default(Mode::all())]", "Mode")
+            ),
+            "default value for property `prop` must be flags like `Mode::Read`, combined with `|`",
+        );
+        assert_program_error(
+            &format!("{} {}", flags, class_with_property("#[default(3)]", "Mode")),
+            "default value for property `prop` must be flags like `Mode::Read`, combined with `|`",
+        );
+
+        assert_program_error(
+            "flags Mode { Read }",
+            "flag `Mode::Read` needs a value like `1 << 2`",
+        );
+        assert_program_error(
+            "flags Mode { Read = -1 }",
+            "value of `Mode::Read` must be an integer literal like `1 << 2`",
+        );
+        assert_program_error(
+            "flags Mode { Read = 1 << 32 }",
+            "value of `Mode::Read` is out of range for `u32`",
+        );
+        assert_program_error(
+            "flags Mode { Read = 1, Read = 2 }",
+            "duplicate variant `Mode::Read`",
+        );
+        assert_program_error(
+            "flags Mode { Read = 1 } enum Mode { Write }",
+            "redefinition of enum `Mode`",
+        );
+    }
+
+    fn checks_boxed() {
+        let boxed = "#[derive(Clone)] boxed struct Color { r: u8, g: u8, b: u8 }";
+
+        assert!(program_is_ok(&format!(
+            "{}
+             boxed Range;
+             class Foo {{}}
+             impl Foo {{
+                 signal fn changed(&self, color: Color, range: Range) -> Color;
+                 pub fn get_color(&self) -> Color {{
+                     Color {{ r: 0, g: 0, b: 0 }}
+                 }}
+             }}",
+            boxed
+        )));
+        assert!(program_is_ok(&format!(
+            "{} {}",
+            boxed,
+            class_with_property("", "Color")
+        )));
+
+        assert_program_error(
+            &format!("{} {}", boxed, class_with_property("#[construct]", "Color")),
+            "boxed property `prop` has no default value, so it can't be set during construction",
+        );
+        assert_program_error(
+            &format!(
+                "{} {}",
+                boxed,
+                class_with_property("#[default(Color::default())]", "Color")
+            ),
+            "boxed property `prop` can't have a default value, minimum or maximum",
+        );
+        assert_program_error(
+            &format!(
+                "{}
+                 class Foo {{}}
+                 impl Foo {{
+                     pub fn set_color(&self, color: &Color) {{
+                     }}
+                 }}",
+                boxed
+            ),
+            "boxed types are passed by value; use `Color` instead of `&Color`",
+        );
+
+        assert_program_error(
+            "#[derive(Clone)] boxed Color;",
+            "`boxed Color;` can't have attributes",
+        );
+        assert_program_error("boxed Color; boxed Color;", "redefinition of type `Color`");
+        assert_program_error(
+            "enum Color { Red } boxed Color;",
+            "redefinition of type `Color`",
+        );
+    }
+
+    fn checks_property_modes() {
+        let getter = "get(&self) -> T { 0 }";
+        let setter = "set(&self, value: T) { }";
+
+        assert!(program_is_ok(&class_with_property_blocks(
+            "", "u32", getter
+        )));
+        assert!(program_is_ok(&class_with_property_blocks(
+            "", "u32", setter
+        )));
+        assert!(program_is_ok(&class_with_property("#[construct]", "u32")));
+        assert!(program_is_ok(&class_with_property(
+            "#[construct_only]",
+            "u32"
+        )));
+
+        assert_program_error(
+            &class_with_property_blocks("", "u32", ""),
+            "property `prop` needs a getter, a setter, or both",
+        );
+        assert_program_error(
+            &class_with_property_blocks("", "u32", &format!("{} {}", getter, getter)),
+            "property `prop` can only have one getter and one setter",
+        );
+        assert_program_error(
+            &class_with_property_blocks("#[construct]", "u32", getter),
+            "property `prop` is set during construction so it needs a setter",
+        );
+        assert_program_error(
+            &class_with_property_blocks("#[construct_only]", "u32", getter),
+            "property `prop` is set during construction so it needs a setter",
+        );
+        assert_program_error(
+            &class_with_property("#[construct] #[construct_only]", "u32"),
+            "property `prop` can't be both #[construct] and #[construct_only]",
+        );
+        assert_program_error(
+            &class_with_property("#[construct(foo)]", "u32"),
+            "`#[construct]` doesn't take any arguments",
+        );
+
+        assert!(program_is_ok(&class_with_property(
+            "#[constructor_arg]",
+            "u32"
+        )));
+        assert!(program_is_ok(&class_with_property(
+            "#[construct_only] #[constructor_arg]",
+            "u32"
+        )));
+        assert_program_error(
+            &class_with_property_blocks("#[constructor_arg]", "u32", getter),
+            "property `prop` is a constructor argument so it needs a setter",
+        );
+        assert_program_error(
+            &class_with_property("#[constructor_arg] #[constructor_arg]", "u32"),
+            "duplicate attribute `constructor_arg` on property `prop`",
+        );
+
+        let builder_clash = |prop: &str, blocks: &str| {
+            format!(
+                "class Foo {{}}
+                 impl Foo {{
+                     property {}: T where T: u32 {{
+                         {}
+                     }}
+                 }}",
+                prop, blocks
+            )
+        };
+        assert!(program_is_ok(&builder_clash("build", getter)));
+        assert_program_error(
+            &builder_clash("build", setter),
+            "writable property `build` would clash with `build()` in the class's builder",
+        );
+        assert_program_error(
+            &builder_clash("set", setter),
+            "writable property `set` would clash with `set()` in the class's builder",
+        );
+
+        assert!(program_is_ok(&class_with_property(
+            "#[explicit_notify]",
+            "u32"
+        )));
+        assert_program_error(
+            &class_with_property("#[explicit_notify = \"yes\"]", "u32"),
+            "`#[explicit_notify]` doesn't take any arguments",
+        );
+    }
 }
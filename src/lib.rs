@@ -116,7 +116,315 @@ mod parser;
 ///
 /// # Declaring signals
 ///
-/// FIXME
+/// A `signal fn` inside `impl Foo` declares a signal.  Its body, if
+/// any, is the signal's default handler; it is stored in a slot of the
+/// class structure, and it runs during emission in the stage given by
+/// the signal's flags (see below).  The `FooExt` trait gets a
+/// `connect_foo()` method for each signal, and the implementation can
+/// call `self.emit_foo()` to emit it.  For signals that return an
+/// object or a boxed type, `emit_foo()` returns an `Option`, which is
+/// `None` if no handler provided a value.
+///
+/// `FooExt` also gets `connect_foo_after()`, whose handlers run after
+/// the default handler; `block_foo()` and `unblock_foo()`, which take
+/// the `SignalHandlerId` returned by the `connect_*()` functions; and
+/// `stop_emission_foo()`, which handlers can call to stop the current
+/// emission.  `Foo::signal_id_foo()` returns the signal's id.
+///
+/// Signals support attributes for their `GSignalFlags`:
+/// `#[run_first]`, `#[run_last]`, `#[run_cleanup]`, `#[no_recurse]`,
+/// `#[detailed]`, `#[action]`, `#[no_hooks]` and `#[deprecated]`.  A
+/// signal without any of the `run_*` attributes is `#[run_last]`.
+/// Signals with a return value must be `#[run_last]`.
+///
+/// A `#[detailed]` signal also gets `self.emit_foo_with_detail(detail,
+/// ...)` and `connect_foo_detailed(detail, f)`; handlers connected with
+/// a detail only run when the signal is emitted with the same detail,
+/// like "notify::prop".
+///
+/// ```norun
+/// impl Foo {
+///     #[run_first]
+///     #[no_recurse]
+///     signal fn clicked(&self);
+/// }
+/// ```
+///
+/// The return value of a signal comes from its last handler, unless
+/// the signal has an `#[accumulator(...)]` to combine the values of all
+/// the handlers.  `#[accumulator(first_wins)]` stops the emission at the
+/// first handler, and `#[accumulator(true_handled)]` stops it at the
+/// first handler that returns `true`, for the usual event handler
+/// pattern.  Any other name refers to a function in scope, which gets
+/// the accumulated value and the value of each handler, and returns
+/// whether the emission should continue:
+///
+/// ```norun
+/// fn sum(accumulated: &mut u32, value: u32) -> bool {
+///     *accumulated += value;
+///     true
+/// }
+///
+/// gobject_gen! {
+///     class Foo {
+///     }
+///
+///     impl Foo {
+///         #[accumulator(sum)]
+///         signal fn count(&self) -> u32;
+///
+///         #[accumulator(true_handled)]
+///         signal fn key_pressed(&self, key: u32) -> bool;
+///     }
+/// }
+/// ```
+///
+/// # Overriding the parent class
+///
+/// A subclass can override the virtual methods and the signal default
+/// handlers of its parent classes in an `impl Parent for Foo` block,
+/// with the same signatures as in the parent.  An override of `foo`
+/// can call `self.parent_foo(...)` to chain up to the implementation in
+/// the parent class, or to its default handler for signals:
+///
+/// ```norun
+/// impl Button for ToggleButton {
+///     virtual fn get_label(&self) -> String {
+///         format!("[{}]", self.parent_get_label().unwrap_or_default())
+///     }
+///
+///     signal fn clicked(&self) {
+///         self.parent_clicked();
+///         self.toggle();
+///     }
+/// }
+/// ```
+///
+/// If the parent's slot is empty, like for a signal without a default
+/// handler or a virtual method that a C class doesn't implement,
+/// `parent_foo()` does nothing for a method or signal without a return
+/// value.  For those with a return value it returns `None` in that
+/// case, and otherwise `Some` with the value from the parent's
+/// implementation.
+///
+/// # Interfaces
+///
+/// An `interface` item declares a `GInterface` with virtual methods.
+/// A method with a body provides the default implementation; the
+/// others must be implemented by the classes that implement the
+/// interface:
+///
+/// ```norun
+/// interface Shape {
+///     virtual fn area(&self) -> f64;
+///
+///     virtual fn describe(&self) -> String {
+///         format!("a shape of area {}", self.area())
+///     }
+/// }
+/// ```
+///
+/// This generates a `Shape` wrapper type, a `ShapeIface` vtable struct
+/// with one slot per method, and a `ShapeExt` trait which calls the
+/// methods through the vtable of the instance's class.  The interface
+/// gets exported C functions like `shape_get_type()` and `shape_area()`.
+///
+/// By default an interface requires `GObject`.  It can list other
+/// prerequisites instead, with at most one class and any number of
+/// interfaces; its wrapper type implements `IsA<>` for each of them.
+/// `glib::Object` can be listed too, but it is implied anyway:
+///
+/// ```norun
+/// interface Polygon: Shape + gio::ListModel {
+///     virtual fn n_sides(&self) -> u32;
+/// }
+/// ```
+///
+/// A class lists the interfaces that it implements after its parent
+/// class, like `class Square: Rectangle, Shape, Polygon`; `class Foo:
+/// Shape` derives from `GObject`.  Prerequisite interfaces must come
+/// first, unless a parent class implements them already, and a class
+/// prerequisite must be the class itself or one of its ancestors.  Each
+/// listed interface needs an `impl interface` block, which must provide
+/// the methods without a default implementation.  Only interfaces
+/// declared in the same `gobject_gen!` can be implemented:
+///
+/// ```norun
+/// impl interface Shape for Square {
+///     virtual fn area(&self) -> f64 {
+///         self.get_side() * self.get_side()
+///     }
+/// }
+/// ```
+///
+/// The interface gets registered for the class when its type is
+/// registered, and the class's wrapper type implements `IsA<Shape>`, so
+/// it gets the methods from `ShapeExt` and can be upcast to `Shape`.
+///
+/// # Types
+///
+/// Arguments and return values of methods and signals can use these
+/// types:
+///
+/// * `bool`, `char`, the integer types, `f32` and `f64`.  GObject has
+/// no 16-bit integer types, so `i16` and `u16` are passed as `gint` and
+/// `guint`; `isize` and `usize` are passed as `glong` and `gulong`,
+/// so code that uses them doesn't build for targets like 64-bit
+/// Windows, where those are narrower.
+///
+/// * `&str` (only for arguments), `String` and `Option<String>`.  In
+/// the C API, string arguments are `const gchar*` and are not owned by
+/// the callee; returned strings are `gchar*` and must be freed by the
+/// caller.  `None` is passed as `NULL`.  Like with
+/// `g_return_val_if_fail()`, a C caller that passes `NULL` for a
+/// non-optional string or object gets a critical warning and a zero
+/// return value.
+///
+/// * `&Foo` and `Foo` for a GObject type `Foo`.  Object arguments are
+/// borrowed by the callee (transfer none); returned objects are owned
+/// by the caller (transfer full).
+///
+/// * `Option<&str>`, `Option<&Foo>` and `Option<Foo>` for values that
+/// may be `NULL` in the C API.  Like their non-optional counterparts,
+/// the borrowed forms can only be used for arguments.
+///
+/// * Enums and flags declared with `enum Foo { ... }` and `flags Foo
+/// { ... }`; see below.
+///
+/// * Boxed types declared with `boxed struct Foo { ... }` or `boxed
+/// Foo;`.  They are passed by value; see below.
+///
+/// Properties can be `bool`, any of the integer types, `f32`, `f64`,
+/// `String`, `Option<String>`, an enum, flags or a boxed type.
+///
+/// # Enumerations
+///
+/// C-like enums declared inside `gobject_gen!` are registered as
+/// `GEnum` types, so they can be used for properties and signal
+/// arguments:
+///
+/// ```norun
+/// enum State {
+///     Idle,
+///     Running = 5,
+///     ShuttingDown,
+/// }
+/// ```
+///
+/// The values of the `GEnum` are named like in C (`STATE_SHUTTING_DOWN`),
+/// and their nicks are the variant names in lower case (`shutting-down`).
+/// The enum implements `glib::StaticType`, `ToValue`, `FromValue`,
+/// `ToGlib` and `FromGlib`, and it gets an exported `state_get_type()`
+/// function.  An enum property uses its first variant as the default
+/// value if it doesn't have a `#[default(State::Running)]` attribute.
+/// Converting an unknown value from C logs a critical warning and
+/// gives the first variant as well.
+///
+/// Likewise, `flags` items are registered as `GFlags` types.  Each flag
+/// needs an explicit value:
+///
+/// ```norun
+/// flags Mode {
+///     Read = 1 << 0,
+///     Write = 1 << 1,
+///     ReadWrite = 1 << 0 | 1 << 1,
+/// }
+/// ```
+///
+/// This generates a `Mode` struct in the style of the `bitflags` crate,
+/// with an associated constant for each flag, `|`, `&`, `^`, `-` and
+/// `!` operators, and methods like `contains()`, `insert()` and
+/// `bits()`.  A flags property defaults to `Mode::empty()`, or to a
+/// combination like `#[default(Mode::Read | Mode::Write)]`.
+///
+/// # Boxed types
+///
+/// A `boxed struct` is registered as a `GBoxed` type, so that plain
+/// Rust structs can be passed through signals, properties and the C
+/// API.  The struct must implement `Clone`, which GObject uses to copy
+/// its values:
+///
+/// ```norun
+/// #[derive(Clone, Debug, Default)]
+/// boxed struct Color {
+///     pub r: u8,
+///     pub g: u8,
+///     pub b: u8,
+/// }
+///
+/// // A type defined elsewhere; it must be `Clone` as well
+/// boxed Range;
+/// ```
+///
+/// The type implements `glib::StaticType`, `ToValue` and `FromValue`,
+/// and it gets an exported `color_get_type()` function.  Boxed values
+/// are allocated with `Box`; in the C API, arguments are `gconstpointer`
+/// and are not owned by the callee, while returned values are `gpointer`
+/// and must be freed with `g_boxed_free()`.  Boxed properties don't
+/// support `#[default]`, `#[construct]` or `#[construct_only]`.
+///
+/// # Properties
+///
+/// Properties are declared inside `impl Foo` with a `get` block, a
+/// `set` block, or both; a property with only one of them is read-only
+/// or write-only, respectively.  The macro installs a `GParamSpec` for
+/// each property, so they work with `g_object_get()`, `g_object_set()`
+/// and "notify" handlers.  The property's GObject name is derived from
+/// the identifier, so `MyProp` and `my_prop` both become `"my-prop"`.
+///
+/// The `FooExt` trait gets `get_my_prop()`, `set_my_prop()` and
+/// `connect_my_prop_notify()` methods for each property.  The class
+/// itself gets `Foo::pspec_my_prop()`, which returns the installed
+/// `GParamSpec`, and the implementation can call `self.notify_my_prop()`
+/// to emit "notify" for the property.
+///
+/// Properties support these attributes:
+///
+/// * `#[nick = "..."]` and `#[blurb = "..."]` - human-readable name
+/// and description of the property, for introspection tools.
+///
+/// * `#[default(...)]` - default value.  It must be a literal of the
+/// property's type.
+///
+/// * `#[min(...)]` and `#[max(...)]` - range of valid values for
+/// numeric properties.
+///
+/// * `#[construct]` - the property gets set to its default value when
+/// the object is created, unless a value is passed to the constructor.
+///
+/// * `#[construct_only]` - the property can only be set when the object
+/// is created, so `FooExt` doesn't get a `set_*()` method for it.
+///
+/// * `#[constructor_arg]` - the property becomes an argument of
+/// `Foo::new()` and of the exported C `foo_new()`, in the order in
+/// which properties are declared.
+///
+/// * `#[explicit_notify]` - GObject doesn't emit "notify" every time
+/// the property is set; the `set` block has to call `self.notify_foo()`
+/// when the value actually changes.
+///
+/// Any writable property can also be set at construction time with
+/// the builder: `Foo::builder().zoom_level(2).build()`.  Properties that
+/// are not passed to the builder keep their default values.  For this
+/// reason, writable properties can't be called `set` or `build`.
+///
+/// ```norun
+/// impl Foo {
+///     #[nick = "Zoom level"]
+///     #[default(1)]
+///     #[min(1)]
+///     #[max(10)]
+///     property zoom_level: T where T: u32 {
+///         get(&self) -> T {
+///             self.get_priv().zoom_level.get()
+///         }
+///
+///         set(&self, value: T) {
+///             self.get_priv().zoom_level.set(value);
+///         }
+///     }
+/// }
+/// ```
 ///
 /// # ABI considerations
 ///
@@ -1,6 +1,8 @@
+use glib_utils::lower_case_instance_name;
+
 mod test;
 
-fn param_name_is_valid(name: &str) -> bool {
+pub fn param_name_is_valid(name: &str) -> bool {
     if name.len() < 1 {
         return false;
     }
@@ -15,3 +17,10 @@ fn param_name_is_valid(name: &str) -> bool {
             || (c >= b'a' && c <= b'z')
     })
 }
+
+/// Turns a Rust identifier into the canonical GObject name for a property.
+///
+/// For example, both `MyProp` and `my_prop` become `my-prop`.
+pub fn canonical_param_name(ident: &str) -> String {
+    lower_case_instance_name(ident).replace('_', "-")
+}
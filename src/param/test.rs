@@ -16,3 +16,10 @@ fn detects_invalid_names() {
     assert!(!param_name_is_valid("foo!"));
     assert!(!param_name_is_valid("123foo"));
 }
+
+#[test]
+fn canonicalizes_names() {
+    assert_eq!(canonical_param_name("MyProp"), "my-prop");
+    assert_eq!(canonical_param_name("my_prop"), "my-prop");
+    assert_eq!(canonical_param_name("prop2"), "prop2");
+}
@@ -33,6 +33,10 @@ impl Synom for ast::Item {
         syn!(ast::Impl) => { |x| ast::Item::Impl(x) }
         |
         syn!(ast::Interface) => { |x| ast::Item::Interface(x) }
+        |
+        syn!(ast::Enum) => { |x| ast::Item::Enum(x) }
+        |
+        syn!(ast::Boxed) => { |x| ast::Item::Boxed(x) }
     ));
 
     fn description() -> Option<&'static str> {
@@ -53,16 +57,27 @@ impl Synom for ast::Class {
     named!(parse -> Self, do_parse!(
         call!(keyword("class"))                                  >>
         name: syn!(Ident)                                        >>
-        extends: option!(do_parse!(
+        parents: option!(do_parse!(
             punct!(:)                                            >>
             superclass: syn!(Path)                               >>
-            // FIXME: interfaces
-            (superclass)))                                       >>
+            interfaces: many0!(do_parse!(
+                punct!(,)                                        >>
+                iface: syn!(Path)                                >>
+                (iface)))                                        >>
+            (superclass, interfaces)))                           >>
         fields: syn!(FieldsNamed)                                >>
-        (ast::Class {
-            name:    name,
-            extends: extends,
-            fields:   fields
+        ({
+            let (extends, implements) = match parents {
+                Some((superclass, interfaces)) => (Some(superclass), interfaces),
+                None => (None, Vec::new()),
+            };
+
+            ast::Class {
+                name:       name,
+                extends:    extends,
+                implements: implements,
+                fields:     fields
+            }
         })
     ));
 
@@ -71,13 +86,29 @@ impl Synom for ast::Class {
     }
 }
 
+// interface Foo [: Prerequisite [+ Prerequisite]*] {
+//     virtual fn bar(&self);
+// }
 impl Synom for ast::Interface {
     named!(parse -> Self, do_parse!(
         call!(keyword("interface")) >>
         name: syn!(Ident) >>
+        requires: option!(do_parse!(
+            punct!(:) >>
+            first: syn!(Path) >>
+            rest: many0!(do_parse!(
+                punct!(+) >>
+                prerequisite: syn!(Path) >>
+                (prerequisite))) >>
+            ({
+                let mut requires = vec![first];
+                requires.extend(rest);
+                requires
+            }))) >>
         items_and_braces: braces!(many0!(syn!(ast::ImplItem)))  >>
         (ast::Interface {
             name: name,
+            requires: requires.unwrap_or_default(),
             items: items_and_braces.1,
         })
     ));
@@ -87,6 +118,74 @@ impl Synom for ast::Interface {
     }
 }
 
+// enum Foo {
+//     A = 0,
+//     B,
+//     ...
+// }
+//
+// flags Foo {
+//     A = 1 << 0,
+//     B = 1 << 1,
+//     ...
+// }
+impl Synom for ast::Enum {
+    named!(parse -> Self, do_parse!(
+        is_flags: alt!(
+            keyword!(enum) => { |_| false }
+            |
+            call!(keyword("flags")) => { |_| true }
+        ) >>
+        name: syn!(Ident) >>
+        variants: braces!(Punctuated::parse_terminated) >>
+        (ast::Enum {
+            is_flags: is_flags,
+            name: name,
+            variants: variants.1,
+        })
+    ));
+
+    fn description() -> Option<&'static str> {
+        Some("enum or flags item")
+    }
+}
+
+// #[derive(Clone)]
+// boxed struct Foo {
+//     ...
+// }
+//
+// boxed ExistingType;
+impl Synom for ast::Boxed {
+    named!(parse -> Self, do_parse!(
+        attrs: many0!(call!(syn::Attribute::parse_outer)) >>
+        call!(keyword("boxed")) >>
+        boxed: alt!(
+            do_parse!(
+                keyword!(struct) >>
+                name: syn!(Ident) >>
+                fields: syn!(FieldsNamed) >>
+                (name, Some(fields))
+            )
+            |
+            do_parse!(
+                name: syn!(Ident) >>
+                punct!(;) >>
+                (name, None)
+            )
+        ) >>
+        (ast::Boxed {
+            attrs: attrs,
+            name: boxed.0,
+            fields: boxed.1,
+        })
+    ));
+
+    fn description() -> Option<&'static str> {
+        Some("boxed item")
+    }
+}
+
 impl Synom for ast::Impl {
     named!(parse -> Self, do_parse!(
         keyword!(impl) >>
@@ -290,12 +389,17 @@ pub mod tests {
     pub fn run() {
         parses_class_with_no_superclass();
         parses_class_with_superclass();
+        parses_class_with_interfaces();
         parses_class_item();
         parses_plain_impl_item();
         parses_impl_item_with_trait();
         parses_class_with_private_field();
         parses_impl_interface();
         parses_interface();
+        parses_interface_with_prerequisites();
+        parses_enum();
+        parses_flags();
+        parses_boxed();
     }
 
     fn assert_tokens_equal<T: ToTokens>(x: &T, s: &str) {
@@ -332,6 +436,16 @@ pub mod tests {
         assert_tokens_equal(&class.extends, "Bar");
     }
 
+    fn parses_class_with_interfaces() {
+        let raw = "class Foo: Bar, Baz, gio::Qux {}";
+        let class = parse_str::<ast::Class>(raw).unwrap();
+
+        assert_tokens_equal(&class.extends, "Bar");
+        assert_eq!(class.implements.len(), 2);
+        assert_tokens_equal(&class.implements[0], "Baz");
+        assert_tokens_equal(&class.implements[1], "gio :: Qux");
+    }
+
     fn parses_class_item() {
         let raw = "class Foo {}";
         let item = parse_str::<ast::Item>(raw).unwrap();
@@ -384,5 +498,65 @@ pub mod tests {
         let iface = parse_str::<ast::Interface>(raw).unwrap();
 
         assert_eq!(iface.name.as_ref(), "Foo");
+        assert!(iface.requires.is_empty());
+    }
+
+    fn parses_interface_with_prerequisites() {
+        let raw = "interface Foo: Bar + Baz { virtual fn bar(&self); }";
+        let iface = parse_str::<ast::Interface>(raw).unwrap();
+
+        assert_eq!(iface.requires.len(), 2);
+        assert_tokens_equal(&iface.requires[0], "Bar");
+        assert_tokens_equal(&iface.requires[1], "Baz");
+    }
+
+    fn parses_enum() {
+        let raw = "enum Foo { A = 0, B, C = 5, }";
+        let item = parse_str::<ast::Item>(raw).unwrap();
+
+        if let ast::Item::Enum(enum_) = item {
+            assert!(!enum_.is_flags);
+            assert_eq!(enum_.name.as_ref(), "Foo");
+            assert_eq!(enum_.variants.len(), 3);
+            assert!(enum_.variants.iter().nth(1).unwrap().discriminant.is_none());
+        } else {
+            unreachable!();
+        }
+    }
+
+    fn parses_flags() {
+        let raw = "flags Foo { A = 1 << 0, B = 1 << 1 }";
+        let item = parse_str::<ast::Item>(raw).unwrap();
+
+        if let ast::Item::Enum(enum_) = item {
+            assert!(enum_.is_flags);
+            assert_eq!(enum_.name.as_ref(), "Foo");
+            assert_eq!(enum_.variants.len(), 2);
+        } else {
+            unreachable!();
+        }
+    }
+
+    fn parses_boxed() {
+        let raw = "#[derive(Clone)] boxed struct Foo { a: u32, b: String }";
+        let item = parse_str::<ast::Item>(raw).unwrap();
+
+        if let ast::Item::Boxed(boxed) = item {
+            assert_eq!(boxed.attrs.len(), 1);
+            assert_eq!(boxed.name.as_ref(), "Foo");
+            assert_eq!(boxed.fields.unwrap().named.len(), 2);
+        } else {
+            unreachable!();
+        }
+
+        let raw = "boxed Foo;";
+        let item = parse_str::<ast::Item>(raw).unwrap();
+
+        if let ast::Item::Boxed(boxed) = item {
+            assert_eq!(boxed.name.as_ref(), "Foo");
+            assert!(boxed.fields.is_none());
+        } else {
+            unreachable!();
+        }
     }
 }
@@ -8,6 +8,7 @@ extern crate glib;
 use gobject_gen::gobject_gen;
 
 extern crate glib_sys;
+extern crate gobject_sys;
 
 gobject_gen! {
     class Foo {
@@ -74,9 +75,95 @@ gobject_gen! {
     }
 }
 
+gobject_gen! {
+    class Strings {
+    }
+
+    impl Strings {
+        pub fn len(&self, s: &str) -> usize {
+            s.len()
+        }
+
+        pub fn concat(&self, a: &str, b: String) -> String {
+            format!("{}{}", a, b)
+        }
+
+        virtual fn maybe_upper(&self, s: Option<String>) -> Option<String> {
+            s.map(|s| s.to_uppercase())
+        }
+    }
+}
+
+gobject_gen! {
+    class Floats {
+    }
+
+    impl Floats {
+        pub fn scale(&self, x: f32, factor: f64) -> f64 {
+            x as f64 * factor
+        }
+
+        virtual fn half(&self, x: f32) -> f32 {
+            x / 2.0
+        }
+    }
+}
+
+gobject_gen! {
+    class Objects {
+    }
+
+    impl Objects {
+        pub fn make_foo(&self) -> Foo {
+            Foo::new()
+        }
+
+        pub fn take_foo(&self, f: Foo) -> u32 {
+            f.method()
+        }
+
+        virtual fn pass_through(&self, o: glib::Object) -> glib::Object {
+            o
+        }
+    }
+}
+
+gobject_gen! {
+    class Nullables {
+    }
+
+    impl Nullables {
+        pub fn str_len(&self, s: Option<&str>) -> u32 {
+            s.map_or(0, |s| s.len() as u32)
+        }
+
+        pub fn foo_method(&self, f: Option<&Foo>) -> u32 {
+            f.map_or(0, |f| f.method())
+        }
+
+        pub fn maybe_foo(&self, make: bool) -> Option<Foo> {
+            if make {
+                Some(Foo::new())
+            } else {
+                None
+            }
+        }
+
+        virtual fn pass_through(&self, o: Option<glib::Object>) -> Option<glib::Object> {
+            o
+        }
+    }
+}
+
+fn ref_count<T: glib::IsA<glib::Object>>(obj: &T) -> u32 {
+    let obj: *mut gobject_sys::GObject = obj.to_glib_none().0;
+    unsafe { *(&(*obj).ref_count as *const _ as *const u32) }
+}
+
 #[test]
 fn test() {
     use glib_sys::*;
+    use std::os::raw::c_ulong;
 
     let t = Test::new();
     assert!(t.two(true, 2));
@@ -98,8 +185,129 @@ fn test() {
     let _: unsafe extern "C" fn(*mut T) = TestMod::imp::test_five;
     let _: unsafe extern "C" fn(*mut T) = TestMod::imp::test_six;
     let _: unsafe extern "C" fn(*mut T, u32) = TestMod::imp::test_seven;
-    let _: unsafe extern "C" fn(*mut T, usize) -> i8 = TestMod::imp::test_eight;
+    let _: unsafe extern "C" fn(*mut T, c_ulong) -> i8 = TestMod::imp::test_eight;
     let _: unsafe extern "C" fn(*mut T, u32) -> u32 = TestMod::imp::test_nine;
     let _: unsafe extern "C" fn(*mut T, *mut F) -> gboolean = TestMod::imp::test_ten;
     let _: unsafe extern "C" fn(*mut T, *mut T) -> gboolean = TestMod::imp::test_eleven;
 }
+
+#[test]
+fn strings() {
+    use glib::translate::*;
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_ulong};
+
+    let s = Strings::new();
+    assert_eq!(s.len("hello"), 5);
+    assert_eq!(s.concat("foo", "bar".to_string()), "foobar");
+    assert_eq!(s.maybe_upper(Some("foo".to_string())), Some("FOO".to_string()));
+    assert_eq!(s.maybe_upper(None), None);
+
+    type S = <Strings as glib::wrapper::Wrapper>::GlibType;
+
+    // Arguments are const c_char* (transfer none); return values are c_char* (transfer full)
+    let _: unsafe extern "C" fn(*mut S, *const c_char) -> c_ulong = StringsMod::imp::strings_len;
+    let _: unsafe extern "C" fn(*mut S, *const c_char, *const c_char) -> *mut c_char =
+        StringsMod::imp::strings_concat;
+    let _: unsafe extern "C" fn(*mut S, *const c_char) -> *mut c_char =
+        StringsMod::imp::strings_maybe_upper;
+
+    unsafe {
+        let a = CString::new("foo").unwrap();
+        let b = CString::new("bar").unwrap();
+        let ret = StringsMod::imp::strings_concat(s.to_glib_none().0, a.as_ptr(), b.as_ptr());
+        assert_eq!(String::from_glib_full(ret), "foobar");
+
+        let ret = StringsMod::imp::strings_maybe_upper(s.to_glib_none().0, std::ptr::null());
+        assert!(ret.is_null());
+
+        // NULL is only allowed for Option, but it doesn't crash otherwise
+        assert_eq!(StringsMod::imp::strings_len(s.to_glib_none().0, std::ptr::null()), 0);
+        let ret = StringsMod::imp::strings_concat(s.to_glib_none().0, a.as_ptr(), std::ptr::null());
+        assert!(ret.is_null());
+    }
+}
+
+#[test]
+fn floats() {
+    let f = Floats::new();
+    assert_eq!(f.scale(1.5, 2.0), 3.0);
+    assert_eq!(f.half(3.0), 1.5);
+
+    type F = <Floats as glib::wrapper::Wrapper>::GlibType;
+
+    let _: unsafe extern "C" fn(*mut F, f32, f64) -> f64 = FloatsMod::imp::floats_scale;
+    let _: unsafe extern "C" fn(*mut F, f32) -> f32 = FloatsMod::imp::floats_half;
+}
+
+#[test]
+fn owned_objects() {
+    use glib::translate::*;
+    use glib::Cast;
+
+    let o = Objects::new();
+
+    let f = o.make_foo();
+    assert_eq!(ref_count(&f), 1);
+
+    // Arguments are transfer none, so the caller keeps its reference
+    let f2 = f.clone();
+    assert_eq!(ref_count(&f2), 2);
+    assert_eq!(o.take_foo(f), 5);
+    assert_eq!(ref_count(&f2), 1);
+
+    // Return values are transfer full
+    let obj = o.pass_through(f2.upcast());
+    assert_eq!(ref_count(&obj), 1);
+
+    type O = <Objects as glib::wrapper::Wrapper>::GlibType;
+    type F = <Foo as glib::wrapper::Wrapper>::GlibType;
+
+    let _: unsafe extern "C" fn(*mut O) -> *mut F = ObjectsMod::imp::objects_make_foo;
+    let _: unsafe extern "C" fn(*mut O, *mut F) -> u32 = ObjectsMod::imp::objects_take_foo;
+    let _: unsafe extern "C" fn(*mut O, *mut gobject_sys::GObject) -> *mut gobject_sys::GObject =
+        ObjectsMod::imp::objects_pass_through;
+
+    unsafe {
+        let f = ObjectsMod::imp::objects_make_foo(o.to_glib_none().0);
+        let f: Foo = from_glib_full(f);
+        assert_eq!(ref_count(&f), 1);
+
+        // NULL is only allowed for Option, but it doesn't crash otherwise
+        assert_eq!(ObjectsMod::imp::objects_take_foo(o.to_glib_none().0, std::ptr::null_mut()), 0);
+    }
+}
+
+#[test]
+fn nullables() {
+    use glib::translate::*;
+    use glib::Cast;
+    use std::ptr;
+
+    let n = Nullables::new();
+    let foo = Foo::new();
+
+    assert_eq!(n.str_len(Some("hello")), 5);
+    assert_eq!(n.str_len(None), 0);
+    assert_eq!(n.foo_method(Some(&foo)), 5);
+    assert_eq!(n.foo_method(None), 0);
+    assert!(n.maybe_foo(false).is_none());
+    assert_eq!(ref_count(&n.maybe_foo(true).unwrap()), 1);
+    assert!(n.pass_through(None).is_none());
+    assert_eq!(ref_count(&n.pass_through(Some(foo.clone().upcast())).unwrap()), 2);
+    assert_eq!(ref_count(&foo), 1);
+
+    type N = <Nullables as glib::wrapper::Wrapper>::GlibType;
+    type F = <Foo as glib::wrapper::Wrapper>::GlibType;
+
+    let _: unsafe extern "C" fn(*mut N, *const std::os::raw::c_char) -> u32 =
+        NullablesMod::imp::nullables_str_len;
+    let _: unsafe extern "C" fn(*mut N, *mut F) -> u32 = NullablesMod::imp::nullables_foo_method;
+
+    unsafe {
+        assert_eq!(NullablesMod::imp::nullables_str_len(n.to_glib_none().0, ptr::null()), 0);
+        assert_eq!(NullablesMod::imp::nullables_foo_method(n.to_glib_none().0, ptr::null_mut()), 0);
+        assert!(NullablesMod::imp::nullables_maybe_foo(n.to_glib_none().0, false.to_glib()).is_null());
+        assert!(NullablesMod::imp::nullables_pass_through(n.to_glib_none().0, ptr::null_mut()).is_null());
+    }
+}
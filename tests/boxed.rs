@@ -0,0 +1,221 @@
+#![deny(warnings)]
+#![feature(proc_macro)]
+
+extern crate gobject_gen;
+extern crate gobject_sys;
+
+#[macro_use]
+extern crate glib;
+
+extern crate glib_sys;
+
+use gobject_gen::gobject_gen;
+use std::cell::RefCell;
+use std::mem;
+use std::panic;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+use glib::object::*;
+use glib::translate::*;
+use glib::{StaticType, ToValue};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Range {
+    start: u32,
+    end: u32,
+}
+
+// Counts live copies, to check that the copy and free functions are balanced
+#[derive(Debug)]
+pub struct Tracked(Rc<()>);
+
+impl Clone for Tracked {
+    fn clone(&self) -> Tracked {
+        Tracked(self.0.clone())
+    }
+}
+
+gobject_gen! {
+    #[derive(Clone, Debug, PartialEq, Default)]
+    boxed struct Color {
+        pub r: u8,
+        pub g: u8,
+        pub b: u8,
+    }
+
+    boxed Range;
+    boxed Tracked;
+
+    class Painter {
+        color: RefCell<Color>,
+    }
+
+    impl Painter {
+        property color: T where T: Color {
+            get(&self) -> T {
+                self.get_priv().color.borrow().clone()
+            }
+
+            set(&self, value: T) {
+                *self.get_priv().color.borrow_mut() = value;
+            }
+        }
+
+        signal fn mix(&self, color: Color, range: Range) -> Color;
+
+        // Without handlers there is no return value
+        pub fn call_emit_mix(&self, color: Color, range: Range) -> Color {
+            self.emit_mix(color, range).unwrap_or_default()
+        }
+
+        pub fn brighten(&self, color: Color, amount: u8) -> Color {
+            Color {
+                r: color.r.saturating_add(amount),
+                g: color.g.saturating_add(amount),
+                b: color.b.saturating_add(amount),
+            }
+        }
+
+        virtual fn keep(&self, t: Tracked) -> Tracked {
+            t
+        }
+
+        virtual fn pick(&self) -> Color {
+            Color::default()
+        }
+    }
+}
+
+#[test]
+fn registers_boxed_types() {
+    let color_type = Color::static_type();
+    assert_eq!(color_type.name(), "Color");
+    assert!(color_type.is_a(&glib::Type::BaseBoxed));
+    assert_eq!(Range::static_type().name(), "Range");
+}
+
+#[test]
+fn converts_to_values() {
+    let color = Color { r: 1, g: 2, b: 3 };
+    let value = color.to_value();
+    assert_eq!(value.type_(), Color::static_type());
+    assert_eq!(value.get::<Color>(), Some(color));
+
+    let range = Range { start: 1, end: 5 };
+    assert_eq!(range.to_value().get::<Range>(), Some(range));
+}
+
+#[test]
+fn passes_boxed_values() {
+    let painter = Painter::new();
+    let color = Color { r: 10, g: 250, b: 0 };
+    assert_eq!(painter.brighten(color, 10), Color { r: 20, g: 255, b: 10 });
+
+    unsafe {
+        let color = Color { r: 1, g: 1, b: 1 };
+        let c = PainterMod::imp::painter_brighten(
+            painter.to_glib_none().0,
+            &color as *const Color as glib_sys::gconstpointer,
+            1,
+        );
+        let c = Box::from_raw(c as *mut Color);
+        assert_eq!(*c, Color { r: 2, g: 2, b: 2 });
+    }
+
+    // Every copy gets freed
+    let counter = Rc::new(());
+    {
+        let t = painter.keep(Tracked(counter.clone()));
+        assert_eq!(Rc::strong_count(&t.0), 2);
+    }
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn uses_boxed_property() {
+    let painter = Painter::new();
+    assert_eq!(painter.get_color(), Color::default());
+
+    let color = Color { r: 1, g: 2, b: 3 };
+    painter.set_color(color.clone());
+    assert_eq!(painter.get_color(), color);
+
+    unsafe {
+        let pspec = Painter::pspec_color();
+        assert_eq!(from_glib::<_, glib::Type>((*pspec).value_type), Color::static_type());
+    }
+}
+
+#[test]
+fn uses_boxed_signal_args() {
+    let painter = Painter::new();
+    let obj_type = painter.get_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"mix\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let color_type = Color::static_type().to_glib();
+        let range_type = Range::static_type().to_glib();
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[color_type, range_type]);
+        assert_eq!(query.return_type, color_type);
+    }
+
+    let mixed = painter.call_emit_mix(Color { r: 1, g: 2, b: 3 }, Range { start: 10, end: 20 });
+    assert_eq!(mixed, Color::default());
+
+    painter.connect_mix(|_, color, range| Color {
+        r: color.r + range.start as u8,
+        g: color.g,
+        b: color.b + range.end as u8,
+    });
+
+    let mixed = painter.call_emit_mix(Color { r: 1, g: 2, b: 3 }, Range { start: 10, end: 20 });
+    assert_eq!(mixed, Color { r: 11, g: 2, b: 23 });
+}
+
+#[test]
+fn ignores_null_boxed_values_from_c() {
+    let painter = Painter::new();
+    let color = Color { r: 1, g: 2, b: 3 };
+    painter.set_color(color.clone());
+
+    unsafe {
+        // A boxed GValue is initialized to NULL
+        let value = glib::Value::from_type(Color::static_type());
+        gobject_sys::g_object_set_property(
+            painter.to_glib_none().0,
+            b"color\0" as *const u8 as *const _,
+            value.to_glib_none().0,
+        );
+        assert_eq!(painter.get_color(), color);
+
+        let c = PainterMod::imp::painter_brighten(painter.to_glib_none().0, ptr::null(), 1);
+        assert!(c.is_null());
+    }
+}
+
+#[test]
+fn rejects_null_boxed_values_returned_from_c() {
+    unsafe extern "C" fn pick_null(_: *mut PainterMod::imp::PainterFfi) -> glib_sys::gpointer {
+        ptr::null_mut()
+    }
+
+    let painter = Painter::new();
+    assert_eq!(painter.pick(), Color::default());
+
+    // Like a subclass implemented in C.  No other test uses pick(), so
+    // this doesn't affect them.
+    unsafe {
+        let klass = gobject_sys::g_type_class_ref(Painter::static_type().to_glib()) as *mut PainterMod::imp::PainterClass;
+        (*klass).pick = Some(pick_null);
+
+        assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| painter.pick())).is_err());
+
+        gobject_sys::g_type_class_unref(klass as *mut _);
+    }
+}
@@ -0,0 +1,150 @@
+#![deny(warnings)]
+#![feature(proc_macro)]
+
+extern crate gobject_gen;
+extern crate gobject_sys;
+
+#[macro_use]
+extern crate glib;
+
+extern crate glib_sys;
+
+use gobject_gen::gobject_gen;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::mem;
+use std::slice;
+
+use glib::object::*;
+use glib::translate::*;
+use glib::{StaticType, ToValue};
+
+gobject_gen! {
+    enum State {
+        Idle,
+        Running = 5,
+        ShuttingDown,
+        Error = -1,
+    }
+
+    class Machine {
+        state: Cell<u32>,
+    }
+
+    impl Machine {
+        #[construct]
+        #[default(State::Running)]
+        property state: T where T: State {
+            get(&self) -> T {
+                from_glib(self.get_priv().state.get() as i32)
+            }
+
+            set(&self, value: T) {
+                self.get_priv().state.set(value.to_glib() as u32);
+            }
+        }
+
+        signal fn changed(&self, old: State, new: State) -> State;
+
+        pub fn change(&self, new: State) -> State {
+            let old = self.get_state();
+            self.set_state(new);
+            self.emit_changed(old, new)
+        }
+    }
+}
+
+#[test]
+fn registers_enum_type() {
+    let gtype = State::static_type();
+    assert_eq!(gtype.name(), "State");
+    assert!(gtype.is_a(&glib::Type::BaseEnum));
+
+    unsafe {
+        let class = gobject_sys::g_type_class_ref(gtype.to_glib()) as *mut gobject_sys::GEnumClass;
+        assert_eq!((*class).n_values, 4);
+        assert_eq!((*class).minimum, -1);
+        assert_eq!((*class).maximum, 6);
+
+        let values = slice::from_raw_parts((*class).values, (*class).n_values as usize);
+        let names: Vec<_> = values
+            .iter()
+            .map(|v| {
+                (
+                    v.value,
+                    CStr::from_ptr(v.value_name).to_str().unwrap(),
+                    CStr::from_ptr(v.value_nick).to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            &[
+                (0, "STATE_IDLE", "idle"),
+                (5, "STATE_RUNNING", "running"),
+                (6, "STATE_SHUTTING_DOWN", "shutting-down"),
+                (-1, "STATE_ERROR", "error"),
+            ]
+        );
+
+        gobject_sys::g_type_class_unref(class as *mut _);
+    }
+}
+
+#[test]
+fn converts_to_values() {
+    assert_eq!(State::ShuttingDown.to_glib(), 6);
+    assert_eq!(State::from_glib(5), State::Running);
+    assert_eq!(State::Error.to_glib(), -1);
+    assert_eq!(State::from_glib(-1), State::Error);
+
+    // Unknown values from C fall back to the first variant
+    assert_eq!(State::from_glib(42), State::Idle);
+
+    let value = State::Running.to_value();
+    assert_eq!(value.type_(), State::static_type());
+    assert_eq!(value.get::<State>(), Some(State::Running));
+}
+
+#[test]
+fn uses_enum_property() {
+    let machine = Machine::new();
+    assert_eq!(machine.get_state(), State::Running);
+
+    machine.set_state(State::Idle);
+    assert_eq!(machine.get_state(), State::Idle);
+
+    unsafe {
+        let pspec = Machine::pspec_state();
+        assert_eq!(from_glib::<_, glib::Type>((*pspec).value_type), State::static_type());
+
+        let pspec = pspec as *mut gobject_sys::GParamSpecEnum;
+        assert_eq!((*pspec).default_value, 5);
+    }
+}
+
+#[test]
+fn uses_enum_signal_args() {
+    let machine = Machine::new();
+    let obj_type = machine.get_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"changed\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let state_type = State::static_type().to_glib();
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[state_type, state_type]);
+        assert_eq!(query.return_type, state_type);
+    }
+
+    machine.connect_changed(|_, old, new| {
+        assert_eq!(old, State::Running);
+        assert_eq!(new, State::ShuttingDown);
+        State::Idle
+    });
+
+    assert_eq!(machine.change(State::ShuttingDown), State::Idle);
+    assert_eq!(machine.get_state(), State::ShuttingDown);
+}
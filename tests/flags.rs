@@ -0,0 +1,158 @@
+#![deny(warnings)]
+#![feature(proc_macro)]
+
+extern crate gobject_gen;
+extern crate gobject_sys;
+
+#[macro_use]
+extern crate glib;
+
+extern crate glib_sys;
+
+use gobject_gen::gobject_gen;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::mem;
+use std::slice;
+
+use glib::object::*;
+use glib::translate::*;
+use glib::{StaticType, ToValue};
+
+gobject_gen! {
+    flags Mode {
+        Read = 1 << 0,
+        Write = 1 << 1,
+        ReadWrite = 1 << 0 | 1 << 1,
+        Execute = 1 << 4,
+    }
+
+    class File {
+        mode: Cell<u32>,
+    }
+
+    impl File {
+        #[construct]
+        #[default(Mode::Read | Mode::Execute)]
+        property mode: T where T: Mode {
+            get(&self) -> T {
+                Mode::from_bits_truncate(self.get_priv().mode.get())
+            }
+
+            set(&self, value: T) {
+                self.get_priv().mode.set(value.bits());
+            }
+        }
+
+        signal fn mode_changed(&self, old: Mode, new: Mode) -> Mode;
+
+        pub fn change_mode(&self, new: Mode) -> Mode {
+            let old = self.get_mode();
+            self.set_mode(new);
+            self.emit_mode_changed(old, new)
+        }
+    }
+}
+
+#[test]
+fn behaves_like_bitflags() {
+    let mut mode = Mode::Read | Mode::Write;
+    assert_eq!(mode, Mode::ReadWrite);
+    assert_eq!(mode.bits(), 3);
+    assert!(mode.contains(Mode::Read));
+    assert!(!mode.contains(Mode::Execute));
+    assert!(mode.intersects(Mode::Read | Mode::Execute));
+
+    mode.remove(Mode::Write);
+    assert_eq!(mode, Mode::Read);
+    mode.insert(Mode::Execute);
+    assert_eq!(mode - Mode::Read, Mode::Execute);
+    assert_eq!(!Mode::ReadWrite, Mode::Execute);
+
+    assert!(Mode::empty().is_empty());
+    assert!(Mode::all().is_all());
+    assert_eq!(Mode::all().bits(), 0x13);
+    assert_eq!(Mode::from_bits(0x4), None);
+    assert_eq!(Mode::from_bits_truncate(0x7), Mode::ReadWrite);
+
+    assert_eq!(format!("{:?}", Mode::Read | Mode::Execute), "Read | Execute");
+    assert_eq!(format!("{:?}", Mode::empty()), "(empty)");
+}
+
+#[test]
+fn registers_flags_type() {
+    let gtype = Mode::static_type();
+    assert_eq!(gtype.name(), "Mode");
+    assert!(gtype.is_a(&glib::Type::BaseFlags));
+
+    unsafe {
+        let class = gobject_sys::g_type_class_ref(gtype.to_glib()) as *mut gobject_sys::GFlagsClass;
+        assert_eq!((*class).mask, 0x13);
+
+        let values = slice::from_raw_parts((*class).values, (*class).n_values as usize);
+        let names: Vec<_> = values
+            .iter()
+            .map(|v| {
+                (
+                    v.value,
+                    CStr::from_ptr(v.value_name).to_str().unwrap(),
+                    CStr::from_ptr(v.value_nick).to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            &[
+                (1, "MODE_READ", "read"),
+                (2, "MODE_WRITE", "write"),
+                (3, "MODE_READ_WRITE", "read-write"),
+                (16, "MODE_EXECUTE", "execute"),
+            ]
+        );
+
+        gobject_sys::g_type_class_unref(class as *mut _);
+    }
+}
+
+#[test]
+fn converts_to_values() {
+    let value = (Mode::Read | Mode::Write).to_value();
+    assert_eq!(value.type_(), Mode::static_type());
+    assert_eq!(value.get::<Mode>(), Some(Mode::ReadWrite));
+}
+
+#[test]
+fn uses_flags_property() {
+    let file = File::new();
+    assert_eq!(file.get_mode(), Mode::Read | Mode::Execute);
+
+    file.set_mode(Mode::Write);
+    assert_eq!(file.get_mode(), Mode::Write);
+
+    unsafe {
+        let pspec = File::pspec_mode() as *mut gobject_sys::GParamSpecFlags;
+        assert_eq!((*pspec).default_value, 0x11);
+    }
+}
+
+#[test]
+fn uses_flags_signal_args() {
+    let file = File::new();
+    let obj_type = file.get_type().to_glib();
+
+    unsafe {
+        let signal_id =
+            gobject_sys::g_signal_lookup(b"mode-changed\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let mode_type = Mode::static_type().to_glib();
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[mode_type, mode_type]);
+        assert_eq!(query.return_type, mode_type);
+    }
+
+    file.connect_mode_changed(|_, old, new| old | new);
+
+    assert_eq!(file.change_mode(Mode::Write), Mode::all());
+}
@@ -1,3 +1,4 @@
+#![deny(warnings)]
 #![feature(proc_macro)]
 
 extern crate gobject_gen;
@@ -11,9 +12,223 @@ extern crate glib_sys;
 extern crate libc;
 
 use gobject_gen::gobject_gen;
+use std::cell::Cell;
+use std::slice;
+
+use glib::object::*;
+use glib::translate::*;
+use glib::StaticType;
 
 gobject_gen! {
     interface Frob {
         virtual fn frob(&self);
+
+        virtual fn frobs(&self, times: u32) -> u32 {
+            times * 2
+        }
+
+        virtual fn describe(&self) -> String {
+            format!("frobs {} times", self.frobs(1))
+        }
+    }
+
+    class Frobber: Frob {
+        count: Cell<u32>,
+    }
+
+    impl Frobber {
+        pub fn count(&self) -> u32 {
+            self.get_priv().count.get()
+        }
+    }
+
+    impl interface Frob for Frobber {
+        virtual fn frob(&self) {
+            let count = self.get_priv().count.get();
+            self.get_priv().count.set(count + 1);
+        }
+    }
+
+    class TripleFrobber: Frobber, Frob {}
+
+    impl interface Frob for TripleFrobber {
+        virtual fn frob(&self) {}
+
+        virtual fn frobs(&self, times: u32) -> u32 {
+            times * 3
+        }
+    }
+
+    interface Resettable: Frob {
+        virtual fn reset(&self);
+
+        virtual fn frob_and_reset(&self) {
+            self.frob();
+            self.reset();
+        }
+    }
+
+    interface Twiddle {
+        virtual fn twiddles(&self) -> u32;
+    }
+
+    class Twiddler: Twiddle {}
+
+    impl interface Twiddle for Twiddler {
+        virtual fn twiddles(&self) -> u32 {
+            7
+        }
+    }
+
+    // The parent class already implements Frob
+    class ResettableFrobber: Frobber, Resettable {
+        resets: Cell<u32>,
+    }
+
+    impl ResettableFrobber {
+        pub fn resets(&self) -> u32 {
+            self.get_priv().resets.get()
+        }
+    }
+
+    impl interface Resettable for ResettableFrobber {
+        virtual fn reset(&self) {
+            let resets = self.get_priv().resets.get();
+            self.get_priv().resets.set(resets + 1);
+        }
+    }
+}
+
+#[test]
+fn registers_interface_type() {
+    let gtype = Frob::static_type();
+    assert_eq!(gtype.name(), "Frob");
+
+    unsafe {
+        assert_eq!(gobject_sys::g_type_fundamental(gtype.to_glib()), gobject_sys::G_TYPE_INTERFACE);
+
+        let mut n_prerequisites = 0;
+        let prerequisites = gobject_sys::g_type_interface_prerequisites(gtype.to_glib(), &mut n_prerequisites);
+        assert_eq!(
+            slice::from_raw_parts(prerequisites, n_prerequisites as usize),
+            &[gobject_sys::g_object_get_type()]
+        );
+        glib_sys::g_free(prerequisites as *mut _);
+    }
+}
+
+#[test]
+fn fills_default_vtable() {
+    unsafe {
+        let iface = gobject_sys::g_type_default_interface_ref(Frob::static_type().to_glib()) as *mut FrobMod::imp::FrobIface;
+
+        assert_eq!((*iface).parent_iface.g_type, Frob::static_type().to_glib());
+        assert!((*iface).frob.is_none());
+        assert!((*iface).frobs.is_some());
+
+        gobject_sys::g_type_default_interface_unref(iface as *mut _);
+    }
+}
+
+#[test]
+fn registers_interface_implementations() {
+    assert!(Frobber::static_type().is_a(&Frob::static_type()));
+    assert!(TripleFrobber::static_type().is_a(&Frob::static_type()));
+
+    unsafe {
+        let mut n_interfaces = 0;
+        let interfaces = gobject_sys::g_type_interfaces(Frobber::static_type().to_glib(), &mut n_interfaces);
+        assert_eq!(
+            slice::from_raw_parts(interfaces, n_interfaces as usize),
+            &[Frob::static_type().to_glib()]
+        );
+        glib_sys::g_free(interfaces as *mut _);
+    }
+}
+
+#[test]
+fn dispatches_through_interface_vtable() {
+    let frobber = Frobber::new();
+    frobber.frob();
+    frobber.frob();
+    assert_eq!(frobber.count(), 2);
+
+    // Default implementations
+    assert_eq!(frobber.frobs(4), 8);
+    assert_eq!(frobber.describe(), "frobs 2 times");
+
+    let triple = TripleFrobber::new();
+    triple.frob();
+    assert_eq!(triple.count(), 0);
+    assert_eq!(triple.frobs(4), 12);
+    assert_eq!(triple.describe(), "frobs 3 times");
+}
+
+#[test]
+fn passes_objects_as_interface() {
+    fn frob_twice<F: IsA<Frob> + IsA<glib::Object>>(f: &F) {
+        f.frob();
+        f.frob();
+    }
+
+    let frobber = Frobber::new();
+    frob_twice(&frobber);
+    assert_eq!(frobber.count(), 2);
+
+    let frob: Frob = frobber.clone().upcast();
+    assert_eq!(frob.frobs(5), 10);
+
+    unsafe {
+        FrobMod::imp::frob_frob(frob.to_glib_none().0);
+        assert_eq!(FrobMod::imp::frob_frobs(frob.to_glib_none().0, 1), 2);
+    }
+    assert_eq!(frobber.count(), 3);
+}
+
+#[test]
+fn requires_prerequisites() {
+    let gtype = Resettable::static_type();
+
+    unsafe {
+        let mut n_prerequisites = 0;
+        let prerequisites = gobject_sys::g_type_interface_prerequisites(gtype.to_glib(), &mut n_prerequisites);
+        {
+            // Frob's own prerequisites get added as well
+            let prerequisites = slice::from_raw_parts(prerequisites, n_prerequisites as usize);
+            assert!(prerequisites.contains(&Frob::static_type().to_glib()));
+            assert!(prerequisites.contains(&gobject_sys::g_object_get_type()));
+        }
+        glib_sys::g_free(prerequisites as *mut _);
+    }
+
+    let frobber = ResettableFrobber::new();
+    assert!(frobber.get_type().is_a(&Resettable::static_type()));
+
+    frobber.frob_and_reset();
+    assert_eq!(frobber.count(), 1);
+    assert_eq!(frobber.resets(), 1);
+
+    let resettable: Resettable = frobber.clone().upcast();
+    resettable.frob();
+    assert_eq!(frobber.count(), 2);
+}
+
+#[test]
+fn ignores_calls_to_empty_slots_from_c() {
+    let twiddler = Twiddler::new();
+    assert_eq!(twiddler.twiddles(), 7);
+
+    // Like a class implemented in C that leaves the slot empty.  No
+    // other test uses Twiddler, so this doesn't affect them.
+    unsafe {
+        let klass = gobject_sys::g_type_class_ref(Twiddler::static_type().to_glib());
+        let iface = gobject_sys::g_type_interface_peek(klass, Twiddle::static_type().to_glib())
+            as *mut TwiddleMod::imp::TwiddleIface;
+        (*iface).twiddles = None;
+
+        let twiddle: Twiddle = twiddler.upcast();
+        assert_eq!(TwiddleMod::imp::twiddle_twiddles(twiddle.to_glib_none().0), 0);
+
+        gobject_sys::g_type_class_unref(klass);
     }
 }
@@ -2,10 +2,15 @@
 #![feature(proc_macro)]
 
 extern crate gobject_gen;
+extern crate gobject_sys;
 
 #[macro_use]
 extern crate glib;
 use gobject_gen::gobject_gen;
+use std::cell::Cell;
+
+use glib::StaticType;
+use glib::translate::*;
 
 gobject_gen! {
     class One {
@@ -19,15 +24,112 @@ gobject_gen! {
         virtual fn get(&self, i: u32, j: u32) -> u32 {
             1 + i + j
         }
+
+        virtual fn describe(&self, prefix: &str) -> String {
+            format!("{}: one", prefix)
+        }
+
+        virtual fn touch(&self) {}
+
+        virtual fn weight(&self) -> u32 {
+            1
+        }
+
+        signal fn clicked(&self, x: u32) -> u32 {
+            x + 1
+        }
+
+        signal fn poked(&self);
+
+        signal fn counted(&self) -> u32;
+
+        pub fn click(&self, x: u32) -> u32 {
+            self.emit_clicked(x)
+        }
+
+        pub fn poke(&self) {
+            self.emit_poked();
+        }
+
+        pub fn count(&self) -> u32 {
+            self.emit_counted()
+        }
     }
 
     class Two: One {
+        poked: Cell<bool>,
+    }
+
+    class Three: Two {
+        touched: Cell<bool>,
+    }
+
+    impl One for Three {
+        virtual fn get(&self, i: u32, j: u32) -> u32 {
+            self.parent_get(i, j).unwrap() * 100
+        }
+
+        virtual fn describe(&self, prefix: &str) -> String {
+            format!("{} and three", self.parent_describe(prefix).unwrap())
+        }
+
+        virtual fn touch(&self) {
+            self.parent_touch();
+            self.get_priv().touched.set(true);
+        }
+
+        virtual fn weight(&self) -> u32 {
+            self.parent_weight().unwrap_or(0) + 10
+        }
+    }
+
+    impl Three {
+        pub fn was_touched(&self) -> bool {
+            self.get_priv().touched.get()
+        }
     }
 
     impl One for Two {
         virtual fn get(&self, i: u32, j: u32) -> u32 {
             2 + i + j
         }
+
+        signal fn clicked(&self, x: u32) -> u32 {
+            self.parent_clicked(x).unwrap() * 10
+        }
+
+        // One has no default handler for this one
+        signal fn counted(&self) -> u32 {
+            self.parent_counted().unwrap_or(100) + 1
+        }
+
+        signal fn poked(&self) {
+            self.parent_poked();
+            self.get_priv().poked.set(true);
+        }
+    }
+
+    impl Two {
+        pub fn was_poked(&self) -> bool {
+            self.get_priv().poked.get()
+        }
+
+        // Three is not IsA<One>, so it can't use OneExt directly
+        pub fn get_from_two(&self, i: u32, j: u32) -> u32 {
+            self.get(i, j)
+        }
+
+        pub fn describe_from_two(&self, prefix: &str) -> String {
+            self.describe(prefix)
+        }
+
+        pub fn touch_from_two(&self) {
+            self.touch()
+        }
+
+        pub fn weight_from_two(&self) -> u32 {
+            self.weight()
+        }
     }
 }
 
@@ -41,3 +143,68 @@ fn test() {
     assert!(two.one() == 1);
     assert!(two.get(0, 0) == 2);
 }
+
+#[test]
+fn overrides_signal_handlers() {
+    let one = One::new();
+    let two = Two::new();
+
+    assert_eq!(one.click(1), 2);
+    assert_eq!(two.click(1), 20);
+
+    // Handlers connected to the subclass still run before the RUN_LAST
+    // class handler
+    two.connect_clicked(|_, x| x);
+    assert_eq!(two.click(2), 30);
+
+    assert!(!two.was_poked());
+    two.poke();
+    assert!(two.was_poked());
+
+    assert_eq!(one.count(), 0);
+    assert_eq!(two.count(), 101);
+}
+
+#[test]
+fn chains_up_to_parent_virtual_methods() {
+    let one = One::new();
+    let two = Two::new();
+    let three = Three::new();
+
+    assert_eq!(one.describe("me"), "me: one");
+    assert_eq!(two.describe("me"), "me: one");
+    assert_eq!(three.describe_from_two("me"), "me: one and three");
+
+    assert_eq!(two.get(1, 1), 4);
+    assert_eq!(three.get_from_two(1, 1), 400);
+}
+
+#[test]
+fn chains_up_to_empty_unit_slot() {
+    // Like a C parent class that leaves a virtual method unimplemented.
+    // No other test uses touch(), so this doesn't affect them.
+    unsafe {
+        let klass = gobject_sys::g_type_class_ref(Two::static_type().to_glib()) as *mut OneMod::imp::OneClass;
+        (*klass).touch = None;
+
+        let three = Three::new();
+        three.touch_from_two();
+        assert!(three.was_touched());
+
+        gobject_sys::g_type_class_unref(klass as *mut _);
+    }
+}
+
+#[test]
+fn chains_up_to_empty_slot_with_return_value() {
+    // No other test uses weight(), so this doesn't affect them
+    unsafe {
+        let klass = gobject_sys::g_type_class_ref(Two::static_type().to_glib()) as *mut OneMod::imp::OneClass;
+        (*klass).weight = None;
+
+        let three = Three::new();
+        assert_eq!(three.weight_from_two(), 10);
+
+        gobject_sys::g_type_class_unref(klass as *mut _);
+    }
+}
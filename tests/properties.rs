@@ -1,12 +1,19 @@
 #![feature(proc_macro)]
 
 extern crate gobject_gen;
+extern crate glib_sys;
+extern crate gobject_sys;
 
 #[macro_use]
 extern crate glib;
 use gobject_gen::gobject_gen;
 
-use std::cell::Cell;
+use glib::object::*;
+use glib::translate::*;
+use glib::ToValue;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+use std::rc::Rc;
 
 gobject_gen! {
     class ClassWithProps {
@@ -46,8 +53,513 @@ gobject_gen! {
     }
 }
 
+gobject_gen! {
+    class PropsWithAttributes {
+        number: Cell<i32>,
+        flag: Cell<bool>,
+        positive: Cell<u8>,
+    }
+
+    impl PropsWithAttributes {
+        #[nick = "Number"]
+        #[blurb = "A number between -10 and 10"]
+        #[default(3)]
+        #[min(-10)]
+        #[max(10)]
+        property number: T where T: i32 {
+            get(&self) -> T {
+                self.get_priv().number.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().number.set(value);
+            }
+        }
+
+        #[default(true)]
+        property flag: T where T: bool {
+            get(&self) -> T {
+                self.get_priv().flag.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().flag.set(value);
+            }
+        }
+
+        #[min(5)]
+        property positive: T where T: u8 {
+            get(&self) -> T {
+                self.get_priv().positive.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().positive.set(value);
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class PropModes {
+        read_only: Cell<u32>,
+        write_only: Cell<u32>,
+        construct_only: Cell<u32>,
+        construct: Cell<u32>,
+    }
+
+    impl PropModes {
+        pub fn get_write_only_value(&self) -> u32 {
+            self.get_priv().write_only.get()
+        }
+
+        pub fn get_construct_value(&self) -> u32 {
+            self.get_priv().construct.get()
+        }
+
+        property read_only: T where T: u32 {
+            get(&self) -> T {
+                42
+            }
+        }
+
+        property write_only: T where T: u32 {
+            set(&self, value: T) {
+                self.get_priv().write_only.set(value);
+            }
+        }
+
+        #[construct_only]
+        #[default(5)]
+        property construct_only: T where T: u32 {
+            get(&self) -> T {
+                self.get_priv().construct_only.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().construct_only.set(value);
+            }
+        }
+
+        #[construct]
+        #[default(7)]
+        property construct: T where T: u32 {
+            set(&self, value: T) {
+                self.get_priv().construct.set(value);
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class WithConstructor {
+        size: Cell<u32>,
+        visible: Cell<bool>,
+        level: Cell<i32>,
+    }
+
+    impl WithConstructor {
+        #[construct_only]
+        #[constructor_arg]
+        property size: T where T: u32 {
+            get(&self) -> T {
+                self.get_priv().size.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().size.set(value);
+            }
+        }
+
+        #[constructor_arg]
+        property visible: T where T: bool {
+            get(&self) -> T {
+                self.get_priv().visible.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().visible.set(value);
+            }
+        }
+
+        #[construct]
+        #[default(-1)]
+        property level: T where T: i32 {
+            get(&self) -> T {
+                self.get_priv().level.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().level.set(value);
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class ExplicitNotify {
+        value: Cell<u32>,
+    }
+
+    impl ExplicitNotify {
+        #[explicit_notify]
+        property value: T where T: u32 {
+            get(&self) -> T {
+                self.get_priv().value.get()
+            }
+
+            set(&self, value: T) {
+                if value != self.get_priv().value.get() {
+                    self.get_priv().value.set(value);
+                    self.notify_value();
+                }
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class StringProps {
+        name: RefCell<String>,
+        nickname: RefCell<Option<String>>,
+    }
+
+    impl StringProps {
+        property name: T where T: String {
+            get(&self) -> T {
+                self.get_priv().name.borrow().clone()
+            }
+
+            set(&self, value: T) {
+                *self.get_priv().name.borrow_mut() = value;
+            }
+        }
+
+        #[construct]
+        #[default("nobody")]
+        property nickname: T where T: Option<String> {
+            get(&self) -> T {
+                self.get_priv().nickname.borrow().clone()
+            }
+
+            set(&self, value: T) {
+                *self.get_priv().nickname.borrow_mut() = value;
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class FloatProps {
+        ratio: Cell<f32>,
+        angle: Cell<f64>,
+    }
+
+    impl FloatProps {
+        #[construct]
+        #[default(0.5)]
+        #[min(0.0)]
+        #[max(1.0)]
+        property ratio: T where T: f32 {
+            get(&self) -> T {
+                self.get_priv().ratio.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().ratio.set(value);
+            }
+        }
+
+        property angle: T where T: f64 {
+            get(&self) -> T {
+                self.get_priv().angle.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().angle.set(value);
+            }
+        }
+    }
+}
+
+gobject_gen! {
+    class IntegerProps {
+        small: Cell<i16>,
+        size: Cell<usize>,
+    }
+
+    impl IntegerProps {
+        #[min(-100)]
+        #[max(100)]
+        property small: T where T: i16 {
+            get(&self) -> T {
+                self.get_priv().small.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().small.set(value);
+            }
+        }
+
+        property size: T where T: usize {
+            get(&self) -> T {
+                self.get_priv().size.get()
+            }
+
+            set(&self, value: T) {
+                self.get_priv().size.set(value);
+            }
+        }
+    }
+}
+
+fn find_pspec<T: IsA<glib::Object>>(obj: &T, name: &str) -> *mut gobject_sys::GParamSpec {
+    unsafe {
+        let instance = obj.to_glib_none().0 as *mut gobject_sys::GTypeInstance;
+        let klass = (*instance).g_class as *mut gobject_sys::GObjectClass;
+        let pspec = gobject_sys::g_object_class_find_property(klass, name.to_glib_none().0);
+        assert!(!pspec.is_null());
+        pspec
+    }
+}
+
 #[test]
 fn test_props() {
     let obj: ClassWithProps = ClassWithProps::new();
     assert_eq!(obj.get(), 0);
 }
+
+#[test]
+fn installs_properties() {
+    let obj: ClassWithProps = ClassWithProps::new();
+
+    assert_eq!(obj.get_property_type("my-prop"), Some(glib::Type::U32));
+    assert_eq!(obj.get_property_type("prop2"), Some(glib::Type::U32));
+    assert_eq!(obj.get_property_type("nonexistent"), None);
+}
+
+#[test]
+fn sets_and_gets_properties() {
+    let obj: ClassWithProps = ClassWithProps::new();
+
+    obj.set_property("my-prop", &5u32.to_value()).unwrap();
+    obj.set_property("prop2", &10u32.to_value()).unwrap();
+    assert_eq!(obj.get(), 15);
+
+    let value = obj.get_property("my-prop").unwrap();
+    assert_eq!(value.get::<u32>(), Some(5));
+
+    let value = obj.get_property("prop2").unwrap();
+    assert_eq!(value.get::<u32>(), Some(10));
+}
+
+#[test]
+fn typed_accessors() {
+    let obj: ClassWithProps = ClassWithProps::new();
+
+    obj.set_my_prop(3);
+    obj.set_prop2(4);
+    assert_eq!(obj.get_my_prop(), 3);
+    assert_eq!(obj.get_prop2(), 4);
+    assert_eq!(obj.get(), 7);
+}
+
+#[test]
+fn connects_to_notify() {
+    let obj: ClassWithProps = ClassWithProps::new();
+
+    let notified = Rc::new(Cell::new(0));
+    let n = notified.clone();
+    obj.connect_my_prop_notify(move |_| n.set(n.get() + 1));
+
+    obj.set_prop2(1);
+    assert_eq!(notified.get(), 0);
+
+    obj.set_my_prop(1);
+    assert_eq!(notified.get(), 1);
+}
+
+#[test]
+fn notifies_explicitly() {
+    let obj = ExplicitNotify::new();
+
+    unsafe {
+        let flags = (*find_pspec(&obj, "value")).flags;
+        assert!(flags.contains(gobject_sys::G_PARAM_EXPLICIT_NOTIFY));
+    }
+
+    let notified = Rc::new(Cell::new(0));
+    let n = notified.clone();
+    obj.connect_value_notify(move |_| n.set(n.get() + 1));
+
+    obj.set_value(0);
+    assert_eq!(notified.get(), 0);
+
+    obj.set_value(1);
+    assert_eq!(notified.get(), 1);
+
+    obj.set_value(1);
+    assert_eq!(notified.get(), 1);
+}
+
+#[test]
+fn exposes_pspecs() {
+    // The class doesn't need to be instantiated first
+    let pspec = ExplicitNotify::pspec_value();
+    assert!(!pspec.is_null());
+
+    let obj = ExplicitNotify::new();
+    assert_eq!(pspec, find_pspec(&obj, "value"));
+
+    let name = unsafe { CStr::from_ptr(gobject_sys::g_param_spec_get_name(pspec)) };
+    assert_eq!(name.to_str().unwrap(), "value");
+}
+
+#[test]
+fn uses_property_attributes() {
+    let obj = PropsWithAttributes::new();
+
+    unsafe {
+        let pspec = find_pspec(&obj, "number");
+        let nick = CStr::from_ptr(gobject_sys::g_param_spec_get_nick(pspec));
+        let blurb = CStr::from_ptr(gobject_sys::g_param_spec_get_blurb(pspec));
+        assert_eq!(nick.to_str().unwrap(), "Number");
+        assert_eq!(blurb.to_str().unwrap(), "A number between -10 and 10");
+
+        let pspec = pspec as *mut gobject_sys::GParamSpecInt;
+        assert_eq!((*pspec).minimum, -10);
+        assert_eq!((*pspec).maximum, 10);
+        assert_eq!((*pspec).default_value, 3);
+
+        let pspec = find_pspec(&obj, "flag") as *mut gobject_sys::GParamSpecBoolean;
+        assert_eq!((*pspec).default_value, glib_sys::GTRUE);
+
+        // No default was given and 0 is out of range, so the minimum is the default
+        let pspec = find_pspec(&obj, "positive") as *mut gobject_sys::GParamSpecUChar;
+        assert_eq!((*pspec).minimum, 5);
+        assert_eq!((*pspec).maximum, 255);
+        assert_eq!((*pspec).default_value, 5);
+    }
+}
+
+#[test]
+fn uses_property_modes() {
+    let obj = PropModes::new();
+
+    unsafe {
+        let flags = |name| (*find_pspec(&obj, name)).flags;
+
+        assert_eq!(flags("read-only"), gobject_sys::G_PARAM_READABLE);
+        assert_eq!(flags("write-only"), gobject_sys::G_PARAM_WRITABLE);
+        assert_eq!(
+            flags("construct-only"),
+            gobject_sys::G_PARAM_READWRITE | gobject_sys::G_PARAM_CONSTRUCT_ONLY
+        );
+        assert_eq!(
+            flags("construct"),
+            gobject_sys::G_PARAM_WRITABLE | gobject_sys::G_PARAM_CONSTRUCT
+        );
+    }
+
+    assert_eq!(obj.get_read_only(), 42);
+
+    obj.set_write_only(3);
+    assert_eq!(obj.get_write_only_value(), 3);
+
+    // Construct properties get their default values during construction
+    assert_eq!(obj.get_construct_only(), 5);
+    assert_eq!(obj.get_construct_value(), 7);
+
+    obj.set_construct(8);
+    assert_eq!(obj.get_construct_value(), 8);
+}
+
+#[test]
+fn passes_constructor_args() {
+    let obj = WithConstructor::new(10, true);
+    assert_eq!(obj.get_size(), 10);
+    assert_eq!(obj.get_visible(), true);
+    assert_eq!(obj.get_level(), -1);
+
+    let obj: WithConstructor =
+        unsafe { from_glib_full(WithConstructorMod::imp::with_constructor_new(20, glib_sys::GFALSE)) };
+    assert_eq!(obj.get_size(), 20);
+    assert_eq!(obj.get_visible(), false);
+}
+
+#[test]
+fn builds_with_properties() {
+    let obj = WithConstructor::builder().level(3).size(4).build();
+    assert_eq!(obj.get_size(), 4);
+    assert_eq!(obj.get_visible(), false);
+    assert_eq!(obj.get_level(), 3);
+
+    // The last value wins if a property is set twice
+    let obj = WithConstructor::builder().size(1).size(2).build();
+    assert_eq!(obj.get_size(), 2);
+    assert_eq!(obj.get_level(), -1);
+}
+
+#[test]
+fn uses_string_properties() {
+    let obj = StringProps::new();
+
+    unsafe {
+        let pspec = find_pspec(&obj, "name");
+        assert_eq!((*pspec).value_type, gobject_sys::G_TYPE_STRING);
+    }
+
+    assert_eq!(obj.get_name(), "");
+    obj.set_name("foo".to_string());
+    assert_eq!(obj.get_name(), "foo");
+
+    assert_eq!(obj.get_nickname(), Some("nobody".to_string()));
+    obj.set_nickname(None);
+    assert_eq!(obj.get_nickname(), None);
+
+    let obj = StringProps::builder().nickname(Some("bar".to_string())).build();
+    assert_eq!(obj.get_nickname(), Some("bar".to_string()));
+}
+
+#[test]
+fn uses_float_properties() {
+    let obj = FloatProps::new();
+
+    unsafe {
+        let pspec = find_pspec(&obj, "ratio") as *mut gobject_sys::GParamSpecFloat;
+        assert_eq!((*pspec).minimum, 0.0);
+        assert_eq!((*pspec).maximum, 1.0);
+        assert_eq!((*pspec).default_value, 0.5);
+
+        let pspec = find_pspec(&obj, "angle");
+        assert_eq!((*pspec).value_type, gobject_sys::G_TYPE_DOUBLE);
+    }
+
+    assert_eq!(obj.get_ratio(), 0.5);
+    obj.set_ratio(0.25);
+    assert_eq!(obj.get_ratio(), 0.25);
+
+    obj.set_angle(-90.0);
+    assert_eq!(obj.get_angle(), -90.0);
+}
+
+#[test]
+fn uses_promoted_integer_properties() {
+    let obj = IntegerProps::new();
+
+    unsafe {
+        let pspec = find_pspec(&obj, "small") as *mut gobject_sys::GParamSpecInt;
+        assert_eq!((*pspec).parent_instance.value_type, gobject_sys::G_TYPE_INT);
+        assert_eq!((*pspec).minimum, -100);
+        assert_eq!((*pspec).maximum, 100);
+
+        let pspec = find_pspec(&obj, "size");
+        assert_eq!((*pspec).value_type, gobject_sys::G_TYPE_ULONG);
+    }
+
+    obj.set_small(-42);
+    assert_eq!(obj.get_small(), -42);
+
+    obj.set_size(1 << 40);
+    assert_eq!(obj.get_size(), 1 << 40);
+}
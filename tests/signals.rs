@@ -11,7 +11,8 @@ extern crate glib_sys;
 extern crate libc;
 
 use gobject_gen::gobject_gen;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::ffi::CStr;
 use std::mem;
 use std::slice;
@@ -47,6 +48,214 @@ gobject_gen! {
     }
 }
 
+gobject_gen! {
+    class StringSignaler {
+    }
+
+    impl StringSignaler {
+        signal fn greet(&self, name: &str) -> String;
+        signal fn nickname(&self) -> Option<String>;
+
+        pub fn call_emit_greet(&self, name: &str) -> String {
+            self.emit_greet(name)
+        }
+
+        pub fn call_emit_nickname(&self) -> Option<String> {
+            self.emit_nickname()
+        }
+    }
+}
+
+gobject_gen! {
+    class FloatSignaler {
+    }
+
+    impl FloatSignaler {
+        signal fn moved(&self, x: f32, y: f64) -> f64;
+
+        pub fn call_emit_moved(&self, x: f32, y: f64) -> f64 {
+            self.emit_moved(x, y)
+        }
+    }
+}
+
+gobject_gen! {
+    class IntegerSignaler {
+    }
+
+    impl IntegerSignaler {
+        signal fn sum(&self, a: i16, b: u16, c: isize, d: usize) -> isize;
+
+        pub fn call_emit_sum(&self, a: i16, b: u16, c: isize, d: usize) -> isize {
+            self.emit_sum(a, b, c, d)
+        }
+    }
+}
+
+gobject_gen! {
+    class ObjectSignaler {
+    }
+
+    impl ObjectSignaler {
+        signal fn pick(&self, a: &Signaler, b: Signaler) -> Signaler;
+
+        pub fn call_emit_pick(&self, a: &Signaler, b: Signaler) -> Option<Signaler> {
+            self.emit_pick(a, b)
+        }
+    }
+}
+
+gobject_gen! {
+    class NullableSignaler {
+    }
+
+    impl NullableSignaler {
+        signal fn pick(&self, name: Option<&str>, a: Option<&Signaler>) -> Option<Signaler>;
+
+        pub fn call_emit_pick(&self, name: Option<&str>, a: Option<&Signaler>) -> Option<Signaler> {
+            self.emit_pick(name, a)
+        }
+    }
+}
+
+gobject_gen! {
+    class FlagsSignaler {
+    }
+
+    impl FlagsSignaler {
+        signal fn plain(&self);
+
+        #[run_first]
+        #[no_recurse]
+        #[detailed]
+        signal fn first(&self);
+
+        #[run_first]
+        #[run_last]
+        #[run_cleanup]
+        #[action]
+        #[no_hooks]
+        #[deprecated]
+        signal fn everything(&self) -> u32;
+    }
+}
+
+fn sum(accumulated: &mut u32, value: u32) -> bool {
+    *accumulated += value;
+    true
+}
+
+fn join(accumulated: &mut String, value: String) -> bool {
+    accumulated.push_str(&value);
+    value != "."
+}
+
+gobject_gen! {
+    class AccumulatorSignaler {
+    }
+
+    impl AccumulatorSignaler {
+        #[accumulator(first_wins)]
+        signal fn first(&self) -> u32;
+
+        #[accumulator(true_handled)]
+        signal fn handled(&self) -> bool;
+
+        #[accumulator(sum)]
+        signal fn total(&self, a: u32) -> u32;
+
+        #[accumulator(join)]
+        signal fn words(&self) -> String;
+
+        pub fn call_emit_first(&self) -> u32 {
+            self.emit_first()
+        }
+
+        pub fn call_emit_handled(&self) -> bool {
+            self.emit_handled()
+        }
+
+        pub fn call_emit_total(&self, a: u32) -> u32 {
+            self.emit_total(a)
+        }
+
+        pub fn call_emit_words(&self) -> String {
+            self.emit_words()
+        }
+    }
+}
+
+gobject_gen! {
+    class DefaultHandlers {
+        log: RefCell<Vec<String>>,
+    }
+
+    impl DefaultHandlers {
+        #[run_first]
+        signal fn first(&self, what: &str) {
+            self.get_priv().log.borrow_mut().push(format!("default {}", what));
+        }
+
+        signal fn last(&self, what: &str) {
+            self.get_priv().log.borrow_mut().push(format!("default {}", what));
+        }
+
+        signal fn answer(&self, a: u32) -> u32 {
+            a * 2
+        }
+
+        signal fn name(&self, obj: &Signaler) -> String {
+            format!("value {}", obj.get_value())
+        }
+
+        pub fn call_emit_first(&self, what: &str) {
+            self.emit_first(what);
+        }
+
+        pub fn call_emit_last(&self, what: &str) {
+            self.emit_last(what);
+        }
+
+        pub fn call_emit_answer(&self, a: u32) -> u32 {
+            self.emit_answer(a)
+        }
+
+        pub fn call_emit_name(&self, obj: &Signaler) -> String {
+            self.emit_name(obj)
+        }
+
+        pub fn log(&self, entry: &str) {
+            self.get_priv().log.borrow_mut().push(entry.to_string());
+        }
+
+        pub fn take_log(&self) -> String {
+            self.get_priv().log.borrow_mut().drain(..).collect::<Vec<_>>().join(", ")
+        }
+    }
+}
+
+gobject_gen! {
+    class DefaultHandlersChild: DefaultHandlers {}
+}
+
+gobject_gen! {
+    class DetailedSignaler {
+    }
+
+    impl DetailedSignaler {
+        #[detailed]
+        signal fn changed(&self, key: &str);
+
+        pub fn call_emit_changed(&self, key: &str) {
+            self.emit_changed(key);
+        }
+
+        pub fn call_emit_changed_with_detail(&self, key: &str) {
+            self.emit_changed_with_detail(key, key);
+        }
+    }
+}
+
 #[cfg(test)]
 fn check_signal(
     query: &gobject_sys::GSignalQuery,
@@ -164,3 +373,290 @@ fn connects_to_signal_with_return_value() {
 
     assert_eq!(ret, 42);
 }
+
+#[test]
+fn connects_to_signal_with_strings() {
+    let obj = StringSignaler::new();
+    let obj_type = obj.get_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"greet\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[gobject_sys::G_TYPE_STRING]);
+        assert_eq!(query.return_type, gobject_sys::G_TYPE_STRING);
+    }
+
+    obj.connect_greet(|_, name| format!("hello, {}", name));
+    assert_eq!(obj.call_emit_greet("world"), "hello, world");
+
+    let id = obj.connect_nickname(|_| None);
+    assert_eq!(obj.call_emit_nickname(), None);
+
+    obj.disconnect(id);
+    obj.connect_nickname(|_| Some("bob".to_string()));
+    assert_eq!(obj.call_emit_nickname(), Some("bob".to_string()));
+}
+
+#[test]
+fn connects_to_signal_with_floats() {
+    let obj = FloatSignaler::new();
+    let obj_type = obj.get_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"moved\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[gobject_sys::G_TYPE_FLOAT, gobject_sys::G_TYPE_DOUBLE]);
+        assert_eq!(query.return_type, gobject_sys::G_TYPE_DOUBLE);
+    }
+
+    obj.connect_moved(|_, x, y| x as f64 + y);
+    assert_eq!(obj.call_emit_moved(0.5, 1.25), 1.75);
+}
+
+#[test]
+fn connects_to_signal_with_promoted_integers() {
+    let obj = IntegerSignaler::new();
+    let obj_type = obj.get_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"sum\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(
+            param_types,
+            &[
+                gobject_sys::G_TYPE_INT,
+                gobject_sys::G_TYPE_UINT,
+                gobject_sys::G_TYPE_LONG,
+                gobject_sys::G_TYPE_ULONG,
+            ]
+        );
+        assert_eq!(query.return_type, gobject_sys::G_TYPE_LONG);
+    }
+
+    obj.connect_sum(|_, a, b, c, d| a as isize + b as isize + c + d as isize);
+    assert_eq!(obj.call_emit_sum(-1, 2, -30000, 4), -29995);
+}
+
+#[test]
+fn connects_to_signal_with_objects() {
+    let obj = ObjectSignaler::new();
+    let obj_type = obj.get_type().to_glib();
+    let signaler_type = <Signaler as glib::StaticType>::static_type().to_glib();
+
+    unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(b"pick\0" as *const u8 as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+
+        let param_types = slice::from_raw_parts(query.param_types, query.n_params as usize);
+        assert_eq!(param_types, &[signaler_type, signaler_type]);
+        assert_eq!(query.return_type, signaler_type);
+    }
+
+    let a = Signaler::new();
+    let b = Signaler::new();
+    a.set_value(1);
+    b.set_value(2);
+
+    // Without handlers there is no return value
+    assert!(obj.call_emit_pick(&a, b.clone()).is_none());
+
+    obj.connect_pick(|_, _, b| b);
+
+    let picked = obj.call_emit_pick(&a, b).unwrap();
+    assert_eq!(picked.get_value(), 2);
+
+    let picked_obj: *mut gobject_sys::GObject = picked.to_glib_none().0;
+    assert_eq!(unsafe { *(&(*picked_obj).ref_count as *const _ as *const u32) }, 1);
+}
+
+#[test]
+fn connects_to_signal_with_nullable_args() {
+    let obj = NullableSignaler::new();
+
+    obj.connect_pick(|_, name, a| match name {
+        Some("a") => a.cloned(),
+        _ => None,
+    });
+
+    let a = Signaler::new();
+    a.set_value(1);
+
+    assert_eq!(obj.call_emit_pick(Some("a"), Some(&a)).unwrap().get_value(), 1);
+    assert!(obj.call_emit_pick(Some("a"), None).is_none());
+    assert!(obj.call_emit_pick(None, Some(&a)).is_none());
+}
+
+#[test]
+fn has_signal_flags() {
+    let obj = FlagsSignaler::new();
+    let obj_type = obj.get_type().to_glib();
+
+    let flags = |name: &[u8]| unsafe {
+        let signal_id = gobject_sys::g_signal_lookup(name as *const [u8] as *const _, obj_type);
+        let mut query: gobject_sys::GSignalQuery = mem::zeroed();
+        gobject_sys::g_signal_query(signal_id, &mut query);
+        query.signal_flags
+    };
+
+    assert_eq!(flags(b"plain\0"), gobject_sys::G_SIGNAL_RUN_LAST);
+    assert_eq!(
+        flags(b"first\0"),
+        gobject_sys::G_SIGNAL_RUN_FIRST | gobject_sys::G_SIGNAL_NO_RECURSE
+            | gobject_sys::G_SIGNAL_DETAILED
+    );
+    assert_eq!(
+        flags(b"everything\0"),
+        gobject_sys::G_SIGNAL_RUN_FIRST | gobject_sys::G_SIGNAL_RUN_LAST
+            | gobject_sys::G_SIGNAL_RUN_CLEANUP | gobject_sys::G_SIGNAL_ACTION
+            | gobject_sys::G_SIGNAL_NO_HOOKS | gobject_sys::G_SIGNAL_DEPRECATED
+    );
+}
+
+#[test]
+fn uses_signal_accumulators() {
+    let obj = AccumulatorSignaler::new();
+    let calls = Rc::new(Cell::new(0));
+
+    for &(n, handled) in &[(1, false), (2, true), (3, true)] {
+        let calls_first = calls.clone();
+        obj.connect_first(move |_| {
+            calls_first.set(calls_first.get() + 1);
+            n
+        });
+        obj.connect_total(move |_, a| a * n);
+        obj.connect_handled(move |_| handled);
+    }
+
+    assert_eq!(obj.call_emit_first(), 1);
+    assert_eq!(calls.get(), 1);
+
+    assert_eq!(obj.call_emit_total(10), 60);
+    assert!(obj.call_emit_handled());
+
+    obj.connect_words(|_| "foo".to_string());
+    obj.connect_words(|_| ".".to_string());
+    obj.connect_words(|_| "bar".to_string());
+    assert_eq!(obj.call_emit_words(), "foo.");
+}
+
+#[test]
+fn runs_default_handlers() {
+    let obj = DefaultHandlers::new();
+
+    obj.call_emit_first("first");
+    obj.call_emit_last("last");
+    assert_eq!(obj.take_log(), "default first, default last");
+
+    obj.connect_first(|obj, what| obj.log(&format!("handler {}", what)));
+    obj.connect_last(|obj, what| obj.log(&format!("handler {}", what)));
+
+    // RUN_FIRST default handlers run before the connected handlers, and
+    // RUN_LAST ones after them
+    obj.call_emit_first("a");
+    obj.call_emit_last("b");
+    assert_eq!(obj.take_log(), "default a, handler a, handler b, default b");
+
+    // The RUN_LAST default handler has the last word on the return value
+    assert_eq!(obj.call_emit_answer(21), 42);
+    obj.connect_answer(|_, a| a + 1);
+    assert_eq!(obj.call_emit_answer(21), 42);
+
+    let signaler = Signaler::new();
+    signaler.set_value(5);
+    assert_eq!(obj.call_emit_name(&signaler), "value 5");
+}
+
+#[test]
+fn connects_to_detailed_signal() {
+    let obj = DetailedSignaler::new();
+    let all = Rc::new(RefCell::new(Vec::new()));
+    let foo = Rc::new(RefCell::new(Vec::new()));
+
+    let all_clone = all.clone();
+    obj.connect_changed(move |_, key| all_clone.borrow_mut().push(key.to_string()));
+
+    let foo_clone = foo.clone();
+    obj.connect_changed_detailed("foo", move |_, key| {
+        foo_clone.borrow_mut().push(key.to_string())
+    });
+
+    obj.call_emit_changed_with_detail("foo");
+    obj.call_emit_changed_with_detail("bar");
+    obj.call_emit_changed("foo");
+
+    assert_eq!(*all.borrow(), &["foo", "bar", "foo"]);
+    assert_eq!(*foo.borrow(), &["foo"]);
+}
+
+#[test]
+fn controls_signal_handlers() {
+    let obj = DefaultHandlers::new();
+
+    let after_id = obj.connect_last_after(|obj, what| obj.log(&format!("after {}", what)));
+    let handler_id = obj.connect_last(|obj, what| obj.log(&format!("handler {}", what)));
+
+    obj.call_emit_last("a");
+    assert_eq!(obj.take_log(), "handler a, default a, after a");
+
+    // Blocking doesn't affect the default handler
+    obj.block_last(&after_id);
+    obj.block_last(&handler_id);
+    obj.call_emit_last("b");
+    assert_eq!(obj.take_log(), "default b");
+
+    obj.unblock_last(&after_id);
+    obj.unblock_last(&handler_id);
+    obj.call_emit_last("c");
+    assert_eq!(obj.take_log(), "handler c, default c, after c");
+
+    obj.connect_first(|obj, what| {
+        obj.log(&format!("handler {}", what));
+        obj.stop_emission_first();
+    });
+    obj.connect_first(|obj, what| obj.log(&format!("not reached {}", what)));
+
+    obj.call_emit_first("d");
+    assert_eq!(obj.take_log(), "default d, handler d");
+}
+
+#[test]
+fn controls_signal_handlers_through_parent_type() {
+    let child = DefaultHandlersChild::new();
+    let handler_id = child.connect_last(|obj, what| obj.log(&format!("handler {}", what)));
+
+    let obj: DefaultHandlers = child.clone().upcast();
+    obj.block_last(&handler_id);
+    child.call_emit_last("a");
+    assert_eq!(child.take_log(), "default a");
+
+    obj.unblock_last(&handler_id);
+    child.call_emit_last("b");
+    assert_eq!(child.take_log(), "handler b, default b");
+}
+
+#[test]
+fn stops_detailed_emission() {
+    let obj = DetailedSignaler::new();
+    let calls = Rc::new(Cell::new(0));
+
+    obj.connect_changed_detailed("foo", |obj, _| obj.stop_emission_changed());
+
+    let calls_clone = calls.clone();
+    obj.connect_changed(move |_, _| calls_clone.set(calls_clone.get() + 1));
+
+    obj.call_emit_changed_with_detail("foo");
+    assert_eq!(calls.get(), 0);
+
+    obj.call_emit_changed_with_detail("bar");
+    assert_eq!(calls.get(), 1);
+}